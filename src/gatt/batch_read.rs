@@ -0,0 +1,165 @@
+use error::Error;
+use gatt::cmd::{read_characteristic_value_by_uuid, read_multiple_characteristic_values};
+use gatt::AttOpcode;
+use message::{Message, MessagePayload};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatchReadEvent {
+    Command(Message),            // Next command to send
+    Values(Vec<(u16, Vec<u8>)>), // Values by characteristic handle
+    Failed(Error),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operation {
+    Idle,
+    ByUuid,
+    Multiple(Vec<(u16, usize)>),
+}
+
+// Reads several characteristic values with one GATT procedure, either all
+// characteristics of a type within a service or a list of handles. Command
+// responses carry no connection handle, so only one instance may have a
+// command in flight on an NCP at a time.
+#[derive(Debug)]
+pub struct BatchRead {
+    connection: u8,
+    operation: Operation,
+    values: Vec<(u16, Vec<u8>)>,
+    data: Vec<u8>,
+    awaiting_response: bool,
+}
+
+impl BatchRead {
+    pub fn new(connection: u8) -> BatchRead {
+        BatchRead {
+            connection,
+            operation: Operation::Idle,
+            values: Vec::new(),
+            data: Vec::new(),
+            awaiting_response: false,
+        }
+    }
+
+    pub fn connection(&self) -> u8 {
+        self.connection
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.operation != Operation::Idle
+    }
+
+    pub fn read_by_uuid(&mut self, service: u32, uuid: Vec<u8>) -> Option<BatchReadEvent> {
+        self.begin(
+            Operation::ByUuid,
+            read_characteristic_value_by_uuid::new(self.connection, service, uuid),
+        )
+    }
+
+    // The response to a multiple read carries the values back to back, so
+    // the length of each one must be known. Only the last may vary in length.
+    pub fn read_multiple(&mut self, characteristics: Vec<(u16, usize)>) -> Option<BatchReadEvent> {
+        let handles = characteristics.iter().map(|x| x.0).collect();
+        self.begin(
+            Operation::Multiple(characteristics),
+            read_multiple_characteristic_values::new(self.connection, handles),
+        )
+    }
+
+    pub fn handle(&mut self, message: &Message) -> Option<BatchReadEvent> {
+        match message.payload {
+            MessagePayload::rsp_gatt_read_characteristic_value_by_uuid(ref x) => {
+                self.response(x.result.clone())
+            }
+            MessagePayload::rsp_gatt_read_multiple_characteristic_values(ref x) => {
+                self.response(x.result.clone())
+            }
+            MessagePayload::evt_gatt_characteristic_value(ref x)
+                if x.connection == self.connection =>
+            {
+                match (&self.operation, &x.att_opcode) {
+                    (Operation::ByUuid, AttOpcode::read_by_type_response) => {
+                        self.values.push((x.characteristic, x.value.clone()))
+                    }
+                    (Operation::Multiple(_), AttOpcode::read_multiple_response) => {
+                        self.data.extend_from_slice(&x.value)
+                    }
+                    _ => (),
+                }
+                None
+            }
+            MessagePayload::evt_gatt_procedure_completed(ref x)
+                if x.connection == self.connection && self.is_busy() =>
+            {
+                match x.result {
+                    Error::success | Error::att_not_found => {
+                        let values = match self.operation {
+                            Operation::Multiple(ref characteristics) => {
+                                split_multiple(characteristics, &self.data)
+                            }
+                            _ => self.values.clone(),
+                        };
+                        self.finish(BatchReadEvent::Values(values))
+                    }
+                    ref error => self.finish(BatchReadEvent::Failed(error.clone())),
+                }
+            }
+            MessagePayload::evt_le_connection_closed(ref x)
+                if x.connection == self.connection && self.is_busy() =>
+            {
+                self.finish(BatchReadEvent::Failed(x.reason.clone()))
+            }
+            _ => None,
+        }
+    }
+
+    fn begin(&mut self, operation: Operation, command: Message) -> Option<BatchReadEvent> {
+        if self.is_busy() {
+            return None;
+        }
+        self.operation = operation;
+        self.values.clear();
+        self.data.clear();
+        self.awaiting_response = true;
+        Some(BatchReadEvent::Command(command))
+    }
+
+    fn response(&mut self, result: Error) -> Option<BatchReadEvent> {
+        if !self.awaiting_response {
+            return None;
+        }
+        self.awaiting_response = false;
+        if result == Error::success {
+            None
+        } else {
+            self.finish(BatchReadEvent::Failed(result))
+        }
+    }
+
+    fn finish(&mut self, event: BatchReadEvent) -> Option<BatchReadEvent> {
+        self.operation = Operation::Idle;
+        self.awaiting_response = false;
+        Some(event)
+    }
+}
+
+// Splits the concatenated values of a multiple read given the expected
+// length of each one. The last value takes the remaining data, and values
+// cut off by the MTU are returned truncated or left out.
+pub fn split_multiple(characteristics: &[(u16, usize)], data: &[u8]) -> Vec<(u16, Vec<u8>)> {
+    let mut values = Vec::new();
+    let mut offset = 0;
+    for (i, &(handle, len)) in characteristics.iter().enumerate() {
+        if offset >= data.len() {
+            break;
+        }
+        let end = if i + 1 == characteristics.len() {
+            data.len()
+        } else {
+            data.len().min(offset + len)
+        };
+        values.push((handle, data[offset..end].to_vec()));
+        offset = end;
+    }
+    values
+}
@@ -0,0 +1,307 @@
+use error::Error;
+use gatt::cmd::read_characteristic_value;
+use gatt::discovery::{Characteristic, Descriptor, Service};
+use gatt::{AttOpcode, CharacteristicProperties};
+use hex::{from_hex, to_hex};
+use message::{Message, MessagePayload};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, BufWriter, ErrorKind, Write};
+use std::path::Path;
+
+const NO_BONDING: u8 = 0xff;
+const SERVICE_CHANGED: [u8; 2] = [0x2a, 0x05];
+const DATABASE_HASH: [u8; 2] = [0x2b, 0x2a];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheEntry {
+    pub address: [u8; 6],
+    pub database_hash: Option<[u8; 16]>,
+    pub services: Vec<Service>,
+}
+
+impl CacheEntry {
+    pub fn service_changed_handle(&self) -> Option<u16> {
+        characteristic_handle(&self.services, &SERVICE_CHANGED)
+    }
+
+    pub fn database_hash_handle(&self) -> Option<u16> {
+        characteristic_handle(&self.services, &DATABASE_HASH)
+    }
+}
+
+fn characteristic_handle(services: &[Service], uuid: &[u8]) -> Option<u16> {
+    services
+        .iter()
+        .filter_map(|x| x.characteristic(uuid))
+        .map(|x| x.handle)
+        .next()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CacheEvent {
+    Invalidated([u8; 6]),
+}
+
+// Discovered GATT databases of bonded peers, keyed by peer address.
+#[derive(Debug, Default)]
+pub struct DiscoveryCache {
+    entries: HashMap<[u8; 6], CacheEntry>,
+    connections: HashMap<u8, [u8; 6]>, // Peer address of each connection
+    bonded: HashSet<u8>,
+}
+
+impl DiscoveryCache {
+    pub fn new() -> DiscoveryCache {
+        DiscoveryCache::default()
+    }
+
+    pub fn insert(
+        &mut self,
+        address: [u8; 6],
+        database_hash: Option<[u8; 16]>,
+        services: Vec<Service>,
+    ) {
+        self.entries.insert(
+            address,
+            CacheEntry {
+                address,
+                database_hash,
+                services,
+            },
+        );
+    }
+
+    // Cached services of a peer. When the current database hash of the peer
+    // is known it must match the cached one.
+    pub fn get(&self, address: &[u8; 6], database_hash: Option<&[u8; 16]>) -> Option<&[Service]> {
+        let entry = self.entries.get(address)?;
+        match (database_hash, entry.database_hash.as_ref()) {
+            (Some(current), Some(cached)) if current != cached => None,
+            _ => Some(&entry.services),
+        }
+    }
+
+    pub fn entry(&self, address: &[u8; 6]) -> Option<&CacheEntry> {
+        self.entries.get(address)
+    }
+
+    pub fn invalidate(&mut self, address: &[u8; 6]) -> bool {
+        self.entries.remove(address).is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    // Peer address of a connection to a bonded device.
+    pub fn peer(&self, connection: u8) -> Option<&[u8; 6]> {
+        if !self.bonded.contains(&connection) {
+            return None;
+        }
+        self.connections.get(&connection)
+    }
+
+    // Cached services for the peer on a connection, if it is bonded.
+    pub fn services(&self, connection: u8) -> Option<&[Service]> {
+        let address = self.peer(connection)?;
+        self.get(address, None)
+    }
+
+    // Reads the Database Hash characteristic of a bonded peer. The value is
+    // compared with the cached one when it arrives, and the entry dropped if
+    // the database has changed.
+    pub fn check_database_hash(&self, connection: u8) -> Option<Message> {
+        let address = self.peer(connection)?;
+        let handle = self.entries.get(address)?.database_hash_handle()?;
+        Some(read_characteristic_value::new(connection, handle))
+    }
+
+    pub fn handle(&mut self, message: &Message) -> Option<CacheEvent> {
+        match message.payload {
+            MessagePayload::evt_le_connection_opened(ref x) => {
+                self.connections.insert(x.connection, x.address);
+                if x.bonding != NO_BONDING {
+                    self.bonded.insert(x.connection);
+                }
+                None
+            }
+            MessagePayload::evt_le_connection_closed(ref x) => {
+                self.connections.remove(&x.connection);
+                self.bonded.remove(&x.connection);
+                None
+            }
+            // Bonding during the connection makes the peer cacheable.
+            MessagePayload::evt_sm_bonded(ref x) if x.bonding != NO_BONDING => {
+                self.bonded.insert(x.connection);
+                None
+            }
+            MessagePayload::evt_gatt_procedure_completed(ref x)
+                if x.result == Error::out_of_sync =>
+            {
+                self.invalidate_connection(x.connection)
+            }
+            MessagePayload::evt_gatt_characteristic_value(ref x)
+                if x.att_opcode == AttOpcode::handle_value_indication =>
+            {
+                let address = *self.peer(x.connection)?;
+                let handle = self.entries.get(&address)?.service_changed_handle()?;
+                if x.characteristic == handle {
+                    self.invalidate_connection(x.connection)
+                } else {
+                    None
+                }
+            }
+            MessagePayload::evt_gatt_characteristic_value(ref x)
+                if x.att_opcode == AttOpcode::read_response && x.value.len() == 16 =>
+            {
+                let address = *self.peer(x.connection)?;
+                let entry = self.entries.get_mut(&address)?;
+                if Some(x.characteristic) != entry.database_hash_handle() {
+                    return None;
+                }
+                let mut hash = [0; 16];
+                hash.copy_from_slice(&x.value);
+                match entry.database_hash {
+                    Some(cached) if cached != hash => self.invalidate_connection(x.connection),
+                    _ => {
+                        entry.database_hash = Some(hash);
+                        None
+                    }
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn invalidate_connection(&mut self, connection: u8) -> Option<CacheEvent> {
+        let address = *self.peer(connection)?;
+        if self.invalidate(&address) {
+            Some(CacheEvent::Invalidated(address))
+        } else {
+            None
+        }
+    }
+
+    // Each peer is stored as a `peer` line followed by `service`,
+    // `characteristic` and `descriptor` lines, with values in hexadecimal.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        let mut entries: Vec<&CacheEntry> = self.entries.values().collect();
+        entries.sort_by_key(|x| x.address);
+        for entry in entries {
+            let hash = match entry.database_hash {
+                Some(ref x) => to_hex(x),
+                None => String::from("-"),
+            };
+            writeln!(file, "peer {} {}", to_hex(&entry.address), hash)?;
+            for service in &entry.services {
+                writeln!(
+                    file,
+                    "service {:08x} {}",
+                    service.handle,
+                    to_hex(&service.uuid)
+                )?;
+                for characteristic in &service.characteristics {
+                    writeln!(
+                        file,
+                        "characteristic {:04x} {:02x} {}",
+                        characteristic.handle,
+                        characteristic.properties.0,
+                        to_hex(&characteristic.uuid)
+                    )?;
+                    for descriptor in &characteristic.descriptors {
+                        writeln!(
+                            file,
+                            "descriptor {:04x} {}",
+                            descriptor.handle,
+                            to_hex(&descriptor.uuid)
+                        )?;
+                    }
+                }
+            }
+        }
+        file.flush()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<DiscoveryCache> {
+        let file = BufReader::new(File::open(path)?);
+        let mut cache = DiscoveryCache::new();
+        let mut entry: Option<CacheEntry> = None;
+        for line in file.lines() {
+            let line = line?;
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                [] => continue,
+                ["peer", address, hash] => {
+                    if let Some(x) = entry.take() {
+                        cache.entries.insert(x.address, x);
+                    }
+                    let mut peer = CacheEntry {
+                        address: [0; 6],
+                        database_hash: None,
+                        services: Vec::new(),
+                    };
+                    peer.address.copy_from_slice(&parse_hex(address, Some(6))?);
+                    if *hash != "-" {
+                        let mut x = [0; 16];
+                        x.copy_from_slice(&parse_hex(hash, Some(16))?);
+                        peer.database_hash = Some(x);
+                    }
+                    entry = Some(peer);
+                }
+                ["service", handle, uuid] => {
+                    let peer = entry.as_mut().ok_or_else(invalid_data)?;
+                    peer.services.push(Service {
+                        handle: u32::from_str_radix(handle, 16).map_err(|_| invalid_data())?,
+                        uuid: parse_hex(uuid, None)?,
+                        characteristics: Vec::new(),
+                    });
+                }
+                ["characteristic", handle, properties, uuid] => {
+                    let service = entry
+                        .as_mut()
+                        .and_then(|x| x.services.last_mut())
+                        .ok_or_else(invalid_data)?;
+                    service.characteristics.push(Characteristic {
+                        handle: u16::from_str_radix(handle, 16).map_err(|_| invalid_data())?,
+                        properties: CharacteristicProperties(
+                            u8::from_str_radix(properties, 16).map_err(|_| invalid_data())?,
+                        ),
+                        uuid: parse_hex(uuid, None)?,
+                        descriptors: Vec::new(),
+                    });
+                }
+                ["descriptor", handle, uuid] => {
+                    let characteristic = entry
+                        .as_mut()
+                        .and_then(|x| x.services.last_mut())
+                        .and_then(|x| x.characteristics.last_mut())
+                        .ok_or_else(invalid_data)?;
+                    characteristic.descriptors.push(Descriptor {
+                        handle: u16::from_str_radix(handle, 16).map_err(|_| invalid_data())?,
+                        uuid: parse_hex(uuid, None)?,
+                    });
+                }
+                _ => return Err(invalid_data()),
+            }
+        }
+        if let Some(x) = entry.take() {
+            cache.entries.insert(x.address, x);
+        }
+        Ok(cache)
+    }
+}
+
+fn invalid_data() -> io::Error {
+    io::Error::from(ErrorKind::InvalidData)
+}
+
+fn parse_hex(text: &str, len: Option<usize>) -> io::Result<Vec<u8>> {
+    match from_hex(text) {
+        Some(ref x) if len.is_some() && len != Some(x.len()) => Err(invalid_data()),
+        Some(x) => Ok(x),
+        None => Err(invalid_data()),
+    }
+}
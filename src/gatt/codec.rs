@@ -0,0 +1,457 @@
+use bytes::{Buf, BufMut};
+use le_connection::ConnectionParameters;
+use std::io::Cursor;
+
+pub const APPEARANCE: [u8; 2] = [0x2a, 0x01];
+pub const PERIPHERAL_PREFERRED_CONNECTION_PARAMETERS: [u8; 2] = [0x2a, 0x04];
+pub const BATTERY_LEVEL: [u8; 2] = [0x2a, 0x19];
+pub const TEMPERATURE_MEASUREMENT: [u8; 2] = [0x2a, 0x1c];
+pub const MODEL_NUMBER: [u8; 2] = [0x2a, 0x24];
+pub const SERIAL_NUMBER: [u8; 2] = [0x2a, 0x25];
+pub const FIRMWARE_REVISION: [u8; 2] = [0x2a, 0x26];
+pub const HARDWARE_REVISION: [u8; 2] = [0x2a, 0x27];
+pub const SOFTWARE_REVISION: [u8; 2] = [0x2a, 0x28];
+pub const MANUFACTURER_NAME: [u8; 2] = [0x2a, 0x29];
+pub const CURRENT_TIME: [u8; 2] = [0x2a, 0x2b];
+pub const HEART_RATE_MEASUREMENT: [u8; 2] = [0x2a, 0x37];
+
+const FLOAT_MANTISSA_MAX: f64 = 8_388_605.0; // 0x7ffffd, above are special values
+const SFLOAT_MANTISSA_MAX: f64 = 2045.0; // 0x07fd
+
+// IEEE-11073 floating point number: mantissa * 10^exponent. Special values
+// use the 24-bit FLOAT mantissas, also when decoded from a 16-bit SFLOAT.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Float {
+    pub mantissa: i32,
+    pub exponent: i8,
+}
+
+impl Float {
+    pub const NAN: Float = Float {
+        mantissa: 0x007f_ffff,
+        exponent: 0,
+    };
+    pub const NRES: Float = Float {
+        mantissa: -0x0080_0000,
+        exponent: 0,
+    };
+    pub const POSITIVE_INFINITY: Float = Float {
+        mantissa: 0x007f_fffe,
+        exponent: 0,
+    };
+    pub const NEGATIVE_INFINITY: Float = Float {
+        mantissa: -0x007f_fffe,
+        exponent: 0,
+    };
+
+    // Rounds a value to the given number of decimal places, expressed as a
+    // negative exponent.
+    pub fn from_f64(value: f64, exponent: i8) -> Float {
+        if value.is_nan() {
+            return Float::NAN;
+        }
+        if value.is_infinite() {
+            return if value > 0.0 {
+                Float::POSITIVE_INFINITY
+            } else {
+                Float::NEGATIVE_INFINITY
+            };
+        }
+        let mantissa = value / 10f64.powi(i32::from(exponent));
+        fit(
+            mantissa,
+            i32::from(exponent),
+            FLOAT_MANTISSA_MAX,
+            (-128, 127),
+        )
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        if self.exponent == 0 {
+            match self.mantissa {
+                0x007f_ffff | -0x0080_0000 | -0x007f_ffff => return f64::NAN,
+                0x007f_fffe => return f64::INFINITY,
+                -0x007f_fffe => return f64::NEG_INFINITY,
+                _ => (),
+            }
+        }
+        f64::from(self.mantissa) * 10f64.powi(i32::from(self.exponent))
+    }
+
+    // 32-bit FLOAT: 8-bit exponent and 24-bit mantissa.
+    pub fn from_float(raw: u32) -> Float {
+        Float {
+            mantissa: ((raw << 8) as i32) >> 8,
+            exponent: (raw >> 24) as i8,
+        }
+    }
+
+    pub fn to_float(&self) -> u32 {
+        let x = if self.is_special() {
+            *self
+        } else {
+            fit(
+                f64::from(self.mantissa),
+                i32::from(self.exponent),
+                FLOAT_MANTISSA_MAX,
+                (-128, 127),
+            )
+        };
+        (x.exponent as u8 as u32) << 24 | (x.mantissa as u32 & 0x00ff_ffff)
+    }
+
+    // 16-bit SFLOAT: 4-bit exponent and 12-bit mantissa.
+    pub fn from_sfloat(raw: u16) -> Float {
+        match raw {
+            0x07ff => return Float::NAN,
+            0x0800 => return Float::NRES,
+            0x07fe => return Float::POSITIVE_INFINITY,
+            0x0802 => return Float::NEGATIVE_INFINITY,
+            _ => (),
+        }
+        Float {
+            mantissa: i32::from(((raw << 4) as i16) >> 4),
+            exponent: ((raw as i16) >> 12) as i8,
+        }
+    }
+
+    pub fn to_sfloat(&self) -> u16 {
+        let x = if self.is_special() {
+            *self
+        } else {
+            fit(
+                f64::from(self.mantissa),
+                i32::from(self.exponent),
+                SFLOAT_MANTISSA_MAX,
+                (-8, 7),
+            )
+        };
+        match x {
+            Float::NAN => 0x07ff,
+            Float::NRES => 0x0800,
+            Float::POSITIVE_INFINITY => 0x07fe,
+            Float::NEGATIVE_INFINITY => 0x0802,
+            _ => (x.exponent as u16) << 12 | (x.mantissa as u16 & 0x0fff),
+        }
+    }
+
+    fn is_special(&self) -> bool {
+        matches!(
+            *self,
+            Float::NAN | Float::NRES | Float::POSITIVE_INFINITY | Float::NEGATIVE_INFINITY
+        )
+    }
+}
+
+// Drops digits from the mantissa, rounding, until it fits and the exponent is
+// in range. Values too large for the exponent become infinite.
+fn fit(mut mantissa: f64, mut exponent: i32, max_mantissa: f64, exponents: (i32, i32)) -> Float {
+    while exponent < exponents.0 || mantissa.round().abs() > max_mantissa {
+        mantissa /= 10.0;
+        exponent += 1;
+    }
+    let mantissa = mantissa.round();
+    if mantissa == 0.0 {
+        Float {
+            mantissa: 0,
+            exponent: exponent.min(exponents.1) as i8,
+        }
+    } else if exponent > exponents.1 {
+        if mantissa > 0.0 {
+            Float::POSITIVE_INFINITY
+        } else {
+            Float::NEGATIVE_INFINITY
+        }
+    } else {
+        Float {
+            mantissa: mantissa as i32,
+            exponent: exponent as i8,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DateTime {
+    pub year: u16, // 0 if not known
+    pub month: u8, // 1 to 12, 0 if not known
+    pub day: u8,   // 1 to 31, 0 if not known
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+}
+
+impl DateTime {
+    fn read(cursor: &mut Cursor<&[u8]>) -> DateTime {
+        DateTime {
+            year: cursor.get_u16_le(),
+            month: cursor.get_u8(),
+            day: cursor.get_u8(),
+            hours: cursor.get_u8(),
+            minutes: cursor.get_u8(),
+            seconds: cursor.get_u8(),
+        }
+    }
+
+    fn write(&self, bytes: &mut Vec<u8>) {
+        bytes.put_u16_le(self.year);
+        bytes.put_u8(self.month);
+        bytes.put_u8(self.day);
+        bytes.put_u8(self.hours);
+        bytes.put_u8(self.minutes);
+        bytes.put_u8(self.seconds);
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HeartRateMeasurement {
+    pub heart_rate: u16,              // Beats per minute
+    pub sensor_contact: Option<bool>, // None if contact detection is not supported
+    pub energy_expended: Option<u16>, // Kilojoules
+    pub rr_intervals: Vec<u16>,       // Units of 1/1024 s
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemperatureMeasurement {
+    pub temperature: Float,
+    pub fahrenheit: bool,
+    pub timestamp: Option<DateTime>,
+    pub temperature_type: Option<u8>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CurrentTime {
+    pub date_time: DateTime,
+    pub day_of_week: u8, // 1 for Monday to 7 for Sunday, 0 if not known
+    pub fractions256: u8,
+    pub adjust_reason: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CharacteristicValue {
+    Appearance(u16),
+    PeripheralPreferredConnectionParameters(ConnectionParameters),
+    BatteryLevel(u8), // Percent
+    TemperatureMeasurement(TemperatureMeasurement),
+    ModelNumber(String),
+    SerialNumber(String),
+    FirmwareRevision(String),
+    HardwareRevision(String),
+    SoftwareRevision(String),
+    ManufacturerName(String),
+    CurrentTime(CurrentTime),
+    HeartRateMeasurement(HeartRateMeasurement),
+}
+
+// Decodes a characteristic value, such as the one carried by
+// evt_gatt_characteristic_value, given the 16-bit UUID of the characteristic.
+// Returns None for other characteristics and malformed values.
+pub fn decode(uuid: &[u8], data: &[u8]) -> Option<CharacteristicValue> {
+    let mut cursor = Cursor::new(data);
+    let value = if uuid == APPEARANCE && data.len() == 2 {
+        CharacteristicValue::Appearance(cursor.get_u16_le())
+    } else if uuid == PERIPHERAL_PREFERRED_CONNECTION_PARAMETERS && data.len() == 8 {
+        CharacteristicValue::PeripheralPreferredConnectionParameters(ConnectionParameters {
+            min_interval: cursor.get_u16_le(),
+            max_interval: cursor.get_u16_le(),
+            latency: cursor.get_u16_le(),
+            timeout: cursor.get_u16_le(),
+        })
+    } else if uuid == BATTERY_LEVEL && data.len() == 1 {
+        CharacteristicValue::BatteryLevel(cursor.get_u8())
+    } else if uuid == TEMPERATURE_MEASUREMENT {
+        CharacteristicValue::TemperatureMeasurement(temperature_measurement(&mut cursor)?)
+    } else if uuid == MODEL_NUMBER {
+        CharacteristicValue::ModelNumber(string(data)?)
+    } else if uuid == SERIAL_NUMBER {
+        CharacteristicValue::SerialNumber(string(data)?)
+    } else if uuid == FIRMWARE_REVISION {
+        CharacteristicValue::FirmwareRevision(string(data)?)
+    } else if uuid == HARDWARE_REVISION {
+        CharacteristicValue::HardwareRevision(string(data)?)
+    } else if uuid == SOFTWARE_REVISION {
+        CharacteristicValue::SoftwareRevision(string(data)?)
+    } else if uuid == MANUFACTURER_NAME {
+        CharacteristicValue::ManufacturerName(string(data)?)
+    } else if uuid == CURRENT_TIME && data.len() == 10 {
+        CharacteristicValue::CurrentTime(CurrentTime {
+            date_time: DateTime::read(&mut cursor),
+            day_of_week: cursor.get_u8(),
+            fractions256: cursor.get_u8(),
+            adjust_reason: cursor.get_u8(),
+        })
+    } else if uuid == HEART_RATE_MEASUREMENT {
+        CharacteristicValue::HeartRateMeasurement(heart_rate_measurement(&mut cursor)?)
+    } else {
+        return None;
+    };
+    Some(value)
+}
+
+fn string(data: &[u8]) -> Option<String> {
+    String::from_utf8(data.to_vec()).ok()
+}
+
+fn temperature_measurement(cursor: &mut Cursor<&[u8]>) -> Option<TemperatureMeasurement> {
+    if cursor.remaining() < 5 {
+        return None;
+    }
+    let flags = cursor.get_u8();
+    let temperature = Float::from_float(cursor.get_u32_le());
+    let timestamp = if flags & 0x02 != 0 {
+        if cursor.remaining() < 7 {
+            return None;
+        }
+        Some(DateTime::read(cursor))
+    } else {
+        None
+    };
+    let temperature_type = if flags & 0x04 != 0 {
+        if cursor.remaining() < 1 {
+            return None;
+        }
+        Some(cursor.get_u8())
+    } else {
+        None
+    };
+    Some(TemperatureMeasurement {
+        temperature,
+        fahrenheit: flags & 0x01 != 0,
+        timestamp,
+        temperature_type,
+    })
+}
+
+fn heart_rate_measurement(cursor: &mut Cursor<&[u8]>) -> Option<HeartRateMeasurement> {
+    if cursor.remaining() < 2 {
+        return None;
+    }
+    let flags = cursor.get_u8();
+    let heart_rate = if flags & 0x01 != 0 {
+        if cursor.remaining() < 2 {
+            return None;
+        }
+        cursor.get_u16_le()
+    } else {
+        u16::from(cursor.get_u8())
+    };
+    let sensor_contact = if flags & 0x04 != 0 {
+        Some(flags & 0x02 != 0)
+    } else {
+        None
+    };
+    let energy_expended = if flags & 0x08 != 0 {
+        if cursor.remaining() < 2 {
+            return None;
+        }
+        Some(cursor.get_u16_le())
+    } else {
+        None
+    };
+    let mut rr_intervals = Vec::new();
+    if flags & 0x10 != 0 {
+        while cursor.remaining() >= 2 {
+            rr_intervals.push(cursor.get_u16_le());
+        }
+    }
+    Some(HeartRateMeasurement {
+        heart_rate,
+        sensor_contact,
+        energy_expended,
+        rr_intervals,
+    })
+}
+
+impl CharacteristicValue {
+    pub fn uuid(&self) -> [u8; 2] {
+        match *self {
+            CharacteristicValue::Appearance(_) => APPEARANCE,
+            CharacteristicValue::PeripheralPreferredConnectionParameters(_) => {
+                PERIPHERAL_PREFERRED_CONNECTION_PARAMETERS
+            }
+            CharacteristicValue::BatteryLevel(_) => BATTERY_LEVEL,
+            CharacteristicValue::TemperatureMeasurement(_) => TEMPERATURE_MEASUREMENT,
+            CharacteristicValue::ModelNumber(_) => MODEL_NUMBER,
+            CharacteristicValue::SerialNumber(_) => SERIAL_NUMBER,
+            CharacteristicValue::FirmwareRevision(_) => FIRMWARE_REVISION,
+            CharacteristicValue::HardwareRevision(_) => HARDWARE_REVISION,
+            CharacteristicValue::SoftwareRevision(_) => SOFTWARE_REVISION,
+            CharacteristicValue::ManufacturerName(_) => MANUFACTURER_NAME,
+            CharacteristicValue::CurrentTime(_) => CURRENT_TIME,
+            CharacteristicValue::HeartRateMeasurement(_) => HEART_RATE_MEASUREMENT,
+        }
+    }
+
+    // Encodes the value, e.g. for gatt_server_write_attribute_value.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        match *self {
+            CharacteristicValue::Appearance(x) => bytes.put_u16_le(x),
+            CharacteristicValue::PeripheralPreferredConnectionParameters(ref x) => {
+                bytes.put_u16_le(x.min_interval);
+                bytes.put_u16_le(x.max_interval);
+                bytes.put_u16_le(x.latency);
+                bytes.put_u16_le(x.timeout);
+            }
+            CharacteristicValue::BatteryLevel(x) => bytes.put_u8(x),
+            CharacteristicValue::TemperatureMeasurement(ref x) => {
+                let mut flags = x.fahrenheit as u8;
+                if x.timestamp.is_some() {
+                    flags |= 0x02;
+                }
+                if x.temperature_type.is_some() {
+                    flags |= 0x04;
+                }
+                bytes.put_u8(flags);
+                bytes.put_u32_le(x.temperature.to_float());
+                if let Some(ref timestamp) = x.timestamp {
+                    timestamp.write(&mut bytes);
+                }
+                if let Some(temperature_type) = x.temperature_type {
+                    bytes.put_u8(temperature_type);
+                }
+            }
+            CharacteristicValue::ModelNumber(ref x)
+            | CharacteristicValue::SerialNumber(ref x)
+            | CharacteristicValue::FirmwareRevision(ref x)
+            | CharacteristicValue::HardwareRevision(ref x)
+            | CharacteristicValue::SoftwareRevision(ref x)
+            | CharacteristicValue::ManufacturerName(ref x) => bytes.extend(x.as_bytes()),
+            CharacteristicValue::CurrentTime(ref x) => {
+                x.date_time.write(&mut bytes);
+                bytes.put_u8(x.day_of_week);
+                bytes.put_u8(x.fractions256);
+                bytes.put_u8(x.adjust_reason);
+            }
+            CharacteristicValue::HeartRateMeasurement(ref x) => {
+                let mut flags = 0;
+                if x.heart_rate > 0xff {
+                    flags |= 0x01;
+                }
+                match x.sensor_contact {
+                    Some(true) => flags |= 0x06,
+                    Some(false) => flags |= 0x04,
+                    None => (),
+                }
+                if x.energy_expended.is_some() {
+                    flags |= 0x08;
+                }
+                if !x.rr_intervals.is_empty() {
+                    flags |= 0x10;
+                }
+                bytes.put_u8(flags);
+                if x.heart_rate > 0xff {
+                    bytes.put_u16_le(x.heart_rate);
+                } else {
+                    bytes.put_u8(x.heart_rate as u8);
+                }
+                if let Some(energy) = x.energy_expended {
+                    bytes.put_u16_le(energy);
+                }
+                for interval in &x.rr_intervals {
+                    bytes.put_u16_le(*interval);
+                }
+            }
+        }
+        bytes
+    }
+}
@@ -0,0 +1,195 @@
+use bytes::Buf;
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use std::io::Cursor;
+
+pub const EXTENDED_PROPERTIES: [u8; 2] = [0x29, 0x00];
+pub const USER_DESCRIPTION: [u8; 2] = [0x29, 0x01];
+pub const CLIENT_CONFIGURATION: [u8; 2] = [0x29, 0x02];
+pub const PRESENTATION_FORMAT: [u8; 2] = [0x29, 0x04];
+pub const VALID_RANGE: [u8; 2] = [0x29, 0x06];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DescriptorValue {
+    ExtendedProperties(ExtendedProperties),
+    UserDescription(String),
+    ClientConfiguration(ClientConfiguration),
+    PresentationFormat(PresentationFormat),
+    ValidRange(ValidRange),
+    Unknown(Vec<u8>), // Unknown descriptor or malformed value
+}
+
+// Decodes the value of a standard descriptor, identified by its UUID as
+// reported in evt_gatt_descriptor.
+pub fn decode(uuid: &[u8], data: &[u8]) -> DescriptorValue {
+    let value = if uuid == EXTENDED_PROPERTIES {
+        ExtendedProperties::from_bytes(data).map(DescriptorValue::ExtendedProperties)
+    } else if uuid == USER_DESCRIPTION {
+        user_description(data).map(DescriptorValue::UserDescription)
+    } else if uuid == CLIENT_CONFIGURATION {
+        ClientConfiguration::from_bytes(data).map(DescriptorValue::ClientConfiguration)
+    } else if uuid == PRESENTATION_FORMAT {
+        PresentationFormat::from_bytes(data).map(DescriptorValue::PresentationFormat)
+    } else if uuid == VALID_RANGE {
+        ValidRange::from_bytes(data).map(DescriptorValue::ValidRange)
+    } else {
+        None
+    };
+    value.unwrap_or_else(|| DescriptorValue::Unknown(data.to_vec()))
+}
+
+pub fn user_description(data: &[u8]) -> Option<String> {
+    String::from_utf8(data.to_vec()).ok()
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ExtendedProperties {
+    pub reliable_write: bool,
+    pub writable_auxiliaries: bool,
+}
+
+impl ExtendedProperties {
+    pub fn from_bytes(data: &[u8]) -> Option<ExtendedProperties> {
+        if data.len() != 2 {
+            return None;
+        }
+        let flags = Cursor::new(data).get_u16_le();
+        Some(ExtendedProperties {
+            reliable_write: flags & 0x0001 != 0,
+            writable_auxiliaries: flags & 0x0002 != 0,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ClientConfiguration {
+    pub notification: bool,
+    pub indication: bool,
+}
+
+impl ClientConfiguration {
+    pub fn from_bytes(data: &[u8]) -> Option<ClientConfiguration> {
+        if data.len() != 2 {
+            return None;
+        }
+        let flags = Cursor::new(data).get_u16_le();
+        Some(ClientConfiguration {
+            notification: flags & 0x0001 != 0,
+            indication: flags & 0x0002 != 0,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        vec![self.notification as u8 | (self.indication as u8) << 1, 0x00]
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, PartialEq, PartialOrd, Clone, FromPrimitive)]
+pub enum Format {
+    boolean = 0x01, // Unsigned 1-bit; 0 = false, 1 = true
+    uint2 = 0x02,   // Unsigned 2-bit integer
+    uint4 = 0x03,   // Unsigned 4-bit integer
+    uint8 = 0x04,   // Unsigned 8-bit integer
+    uint12 = 0x05,  // Unsigned 12-bit integer
+    uint16 = 0x06,  // Unsigned 16-bit integer
+    uint24 = 0x07,  // Unsigned 24-bit integer
+    uint32 = 0x08,  // Unsigned 32-bit integer
+    uint48 = 0x09,  // Unsigned 48-bit integer
+    uint64 = 0x0a,  // Unsigned 64-bit integer
+    uint128 = 0x0b, // Unsigned 128-bit integer
+    sint8 = 0x0c,   // Signed 8-bit integer
+    sint12 = 0x0d,  // Signed 12-bit integer
+    sint16 = 0x0e,  // Signed 16-bit integer
+    sint24 = 0x0f,  // Signed 24-bit integer
+    sint32 = 0x10,  // Signed 32-bit integer
+    sint48 = 0x11,  // Signed 48-bit integer
+    sint64 = 0x12,  // Signed 64-bit integer
+    sint128 = 0x13, // Signed 128-bit integer
+    float32 = 0x14, // IEEE-754 32-bit floating point
+    float64 = 0x15, // IEEE-754 64-bit floating point
+    sfloat = 0x16,  // IEEE-11073 16-bit SFLOAT
+    float = 0x17,   // IEEE-11073 32-bit FLOAT
+    duint16 = 0x18, // IEEE-20601 format
+    utf8s = 0x19,   // UTF-8 string
+    utf16s = 0x1a,  // UTF-16 string
+    opaque = 0x1b,  // Opaque structure
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PresentationFormat {
+    pub format: Format,
+    pub exponent: i8,
+    pub unit: u16, // Bluetooth SIG assigned unit UUID, e.g. 0x272f for degrees Celsius
+    pub namespace: u8,
+    pub description: u16,
+}
+
+impl PresentationFormat {
+    pub fn from_bytes(data: &[u8]) -> Option<PresentationFormat> {
+        if data.len() != 7 {
+            return None;
+        }
+        let mut cursor = Cursor::new(data);
+        let format = FromPrimitive::from_u8(cursor.get_u8())?;
+        let exponent = cursor.get_i8();
+        let unit = cursor.get_u16_le();
+        let namespace = cursor.get_u8();
+        let description = cursor.get_u16_le();
+        Some(PresentationFormat {
+            format,
+            exponent,
+            unit,
+            namespace,
+            description,
+        })
+    }
+
+    // Applies the exponent to a raw integer value: value * 10^exponent.
+    pub fn scale(&self, value: i64) -> f64 {
+        value as f64 * 10f64.powi(i32::from(self.exponent))
+    }
+}
+
+// Inclusive bounds, encoded in the format of the characteristic value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidRange {
+    pub lower: Vec<u8>,
+    pub upper: Vec<u8>,
+}
+
+impl ValidRange {
+    pub fn from_bytes(data: &[u8]) -> Option<ValidRange> {
+        if data.is_empty() || data.len() % 2 == 1 {
+            return None;
+        }
+        let (lower, upper) = data.split_at(data.len() / 2);
+        Some(ValidRange {
+            lower: lower.to_vec(),
+            upper: upper.to_vec(),
+        })
+    }
+
+    // Bounds of an integer format of up to 8 bytes, little endian.
+    pub fn bounds(&self, signed: bool) -> Option<(i64, i64)> {
+        Some((
+            to_integer(&self.lower, signed)?,
+            to_integer(&self.upper, signed)?,
+        ))
+    }
+}
+
+fn to_integer(data: &[u8], signed: bool) -> Option<i64> {
+    if data.is_empty() || data.len() > 8 {
+        return None;
+    }
+    let mut value = data
+        .iter()
+        .rev()
+        .fold(0u64, |acc, x| acc << 8 | u64::from(*x));
+    let bits = data.len() * 8;
+    if signed && bits < 64 && value & (1 << (bits - 1)) != 0 {
+        value |= !0u64 << bits;
+    }
+    Some(value as i64)
+}
@@ -0,0 +1,253 @@
+use error::Error;
+use gatt::cmd::{
+    discover_characteristics, discover_descriptors, discover_primary_services,
+    discover_primary_services_by_uuid,
+};
+use gatt::CharacteristicProperties;
+use message::{Message, MessagePayload};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Descriptor {
+    pub handle: u16,
+    pub uuid: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Characteristic {
+    pub handle: u16,
+    pub properties: CharacteristicProperties,
+    pub uuid: Vec<u8>,
+    pub descriptors: Vec<Descriptor>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Service {
+    pub handle: u32,
+    pub uuid: Vec<u8>,
+    pub characteristics: Vec<Characteristic>,
+}
+
+impl Service {
+    // The service handle carries the first attribute handle of the service in
+    // its lower half and the last one in its upper half.
+    pub fn start_handle(&self) -> u16 {
+        self.handle as u16
+    }
+
+    pub fn end_handle(&self) -> u16 {
+        (self.handle >> 16) as u16
+    }
+
+    pub fn characteristic(&self, uuid: &[u8]) -> Option<&Characteristic> {
+        self.characteristics.iter().find(|x| x.uuid == uuid)
+    }
+}
+
+pub fn find_service<'a>(services: &'a [Service], uuid: &[u8]) -> Option<&'a Service> {
+    services.iter().find(|x| x.uuid == uuid)
+}
+
+pub fn find_characteristic<'a>(services: &'a [Service], uuid: &[u8]) -> Option<&'a Characteristic> {
+    services
+        .iter()
+        .filter_map(|x| x.characteristic(uuid))
+        .next()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiscoveryEvent {
+    Command(Message), // Next command to send
+    Complete(Vec<Service>),
+    Failed(Error),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Idle,
+    Services(usize),
+    Characteristics(usize),
+    Descriptors(usize, usize),
+    Done,
+}
+
+// Discovers the services, characteristics and descriptors of a peer by
+// chaining GATT procedures, each one finished by evt_gatt_procedure_completed.
+// Command responses carry no connection handle, so only one discovery may
+// have a command in flight on an NCP at a time; discover peers one by one.
+#[derive(Debug)]
+pub struct Discovery {
+    connection: u8,
+    uuids: Option<Vec<Vec<u8>>>,
+    services: Vec<Service>,
+    step: Step,
+    awaiting_response: bool,
+}
+
+impl Discovery {
+    pub fn new(connection: u8) -> Discovery {
+        Discovery {
+            connection,
+            uuids: None,
+            services: Vec::new(),
+            step: Step::Idle,
+            awaiting_response: false,
+        }
+    }
+
+    // Discovers only the services with the given UUIDs.
+    pub fn with_services(connection: u8, uuids: Vec<Vec<u8>>) -> Discovery {
+        Discovery {
+            uuids: Some(uuids),
+            ..Discovery::new(connection)
+        }
+    }
+
+    pub fn connection(&self) -> u8 {
+        self.connection
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.step == Step::Done
+    }
+
+    pub fn start(&mut self) -> Option<DiscoveryEvent> {
+        self.services.clear();
+        self.step = Step::Services(0);
+        self.next()
+    }
+
+    pub fn handle(&mut self, message: &Message) -> Option<DiscoveryEvent> {
+        if self.step == Step::Idle || self.step == Step::Done {
+            return None;
+        }
+        match message.payload {
+            MessagePayload::rsp_gatt_discover_primary_services(ref x) => {
+                self.response(x.result.clone())
+            }
+            MessagePayload::rsp_gatt_discover_primary_services_by_uuid(ref x) => {
+                self.response(x.result.clone())
+            }
+            MessagePayload::rsp_gatt_discover_characteristics(ref x) => {
+                self.response(x.result.clone())
+            }
+            MessagePayload::rsp_gatt_discover_descriptors(ref x) => self.response(x.result.clone()),
+            MessagePayload::evt_gatt_service(ref x) if x.connection == self.connection => {
+                if let Step::Services(_) = self.step {
+                    self.services.push(Service {
+                        handle: x.service,
+                        uuid: x.uuid.clone(),
+                        characteristics: Vec::new(),
+                    });
+                }
+                None
+            }
+            MessagePayload::evt_gatt_characteristic(ref x) if x.connection == self.connection => {
+                if let Step::Characteristics(service) = self.step {
+                    self.services[service].characteristics.push(Characteristic {
+                        handle: x.characteristic,
+                        properties: x.properties,
+                        uuid: x.uuid.clone(),
+                        descriptors: Vec::new(),
+                    });
+                }
+                None
+            }
+            MessagePayload::evt_gatt_descriptor(ref x) if x.connection == self.connection => {
+                if let Step::Descriptors(service, characteristic) = self.step {
+                    self.services[service].characteristics[characteristic]
+                        .descriptors
+                        .push(Descriptor {
+                            handle: x.descriptor,
+                            uuid: x.uuid.clone(),
+                        });
+                }
+                None
+            }
+            MessagePayload::evt_gatt_procedure_completed(ref x)
+                if x.connection == self.connection =>
+            {
+                match x.result {
+                    Error::success | Error::att_not_found => {
+                        self.advance();
+                        self.next()
+                    }
+                    ref error => self.fail(error.clone()),
+                }
+            }
+            MessagePayload::evt_le_connection_closed(ref x) if x.connection == self.connection => {
+                self.fail(x.reason.clone())
+            }
+            _ => None,
+        }
+    }
+
+    fn response(&mut self, result: Error) -> Option<DiscoveryEvent> {
+        if !self.awaiting_response {
+            return None;
+        }
+        self.awaiting_response = false;
+        if result == Error::success {
+            None
+        } else {
+            self.fail(result)
+        }
+    }
+
+    fn fail(&mut self, error: Error) -> Option<DiscoveryEvent> {
+        self.step = Step::Done;
+        self.awaiting_response = false;
+        Some(DiscoveryEvent::Failed(error))
+    }
+
+    fn advance(&mut self) {
+        self.step = match self.step {
+            Step::Services(n) => match self.uuids {
+                Some(ref uuids) if n + 1 < uuids.len() => Step::Services(n + 1),
+                _ => Step::Characteristics(0),
+            },
+            Step::Characteristics(service) => Step::Descriptors(service, 0),
+            Step::Descriptors(service, characteristic) => {
+                Step::Descriptors(service, characteristic + 1)
+            }
+            ref step => step.clone(),
+        };
+    }
+
+    // Issues the command for the current step, skipping steps with nothing
+    // to discover.
+    fn next(&mut self) -> Option<DiscoveryEvent> {
+        loop {
+            let command = match self.step {
+                Step::Services(n) => match self.uuids {
+                    Some(ref uuids) if n < uuids.len() => {
+                        discover_primary_services_by_uuid::new(self.connection, uuids[n].clone())
+                    }
+                    Some(_) => {
+                        self.step = Step::Characteristics(0);
+                        continue;
+                    }
+                    None => discover_primary_services::new(self.connection),
+                },
+                Step::Characteristics(service) if service < self.services.len() => {
+                    discover_characteristics::new(self.connection, self.services[service].handle)
+                }
+                Step::Descriptors(service, characteristic)
+                    if characteristic < self.services[service].characteristics.len() =>
+                {
+                    let handle = self.services[service].characteristics[characteristic].handle;
+                    discover_descriptors::new(self.connection, handle)
+                }
+                Step::Descriptors(service, _) => {
+                    self.step = Step::Characteristics(service + 1);
+                    continue;
+                }
+                _ => {
+                    self.step = Step::Done;
+                    return Some(DiscoveryEvent::Complete(self.services.clone()));
+                }
+            };
+            self.awaiting_response = true;
+            return Some(DiscoveryEvent::Command(command));
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use bytes::{Buf, BufMut};
 use error::Error;
-use gatt::AttOpcode;
+use gatt::{AttOpcode, CharacteristicProperties};
 use num_traits::FromPrimitive;
 use std::io::{Cursor, Read};
 
@@ -9,7 +9,7 @@ use std::io::{Cursor, Read};
 pub struct characteristic {
     pub connection: u8,
     pub characteristic: u16,
-    pub properties: u8,
+    pub properties: CharacteristicProperties,
     pub uuid: Vec<u8>,
 }
 
@@ -18,7 +18,7 @@ impl From<&[u8]> for characteristic {
         let mut cursor = Cursor::new(data);
         let connection = cursor.get_u8();
         let characteristic = cursor.get_u16_le();
-        let properties = cursor.get_u8();
+        let properties = CharacteristicProperties(cursor.get_u8());
         let mut uuid: Vec<u8> = Vec::new();
         cursor.get_u8();
         cursor
@@ -39,7 +39,7 @@ impl Into<Vec<u8>> for characteristic {
         let mut bytes = Vec::new();
         bytes.put_u8(self.connection);
         bytes.put_u16_le(self.characteristic);
-        bytes.put_u8(self.properties);
+        bytes.put_u8(self.properties.0);
         bytes.put_u8(self.uuid.len() as u8);
         bytes.extend(self.uuid.iter().rev());
         bytes
@@ -0,0 +1,347 @@
+use error::Error;
+use gatt::cmd::{
+    execute_characteristic_value_write, prepare_characteristic_value_reliable_write,
+    prepare_characteristic_value_write, read_characteristic_value_from_offset,
+};
+use gatt::{execute_write_flag, AttOpcode};
+use message::{Message, MessagePayload};
+
+const DEFAULT_MTU: u16 = 23;
+const READ_HEADER: u16 = 1; // Opcode
+const PREPARE_WRITE_HEADER: u16 = 5; // Opcode, handle and offset
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LongValueEvent {
+    Command(Message), // Next command to send
+    ReadComplete(Vec<u8>),
+    WriteComplete,
+    Mismatch(Fragment), // Reliable write cancelled after a wrong echo
+    Failed(Error),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fragment {
+    pub offset: u16,
+    pub expected: Vec<u8>,
+    pub received: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operation {
+    Idle,
+    Read {
+        characteristic: u16,
+        offset: usize,
+        value: Vec<u8>,
+    },
+    Write {
+        characteristic: u16,
+        offset: usize,
+        value: Vec<u8>,
+        reliable: bool,
+        mismatch: Option<Fragment>,
+    },
+    Execute,
+    Cancel(LongValueEvent), // Reported once the queued writes are cancelled
+}
+
+// Reads and writes characteristic values longer than one ATT PDU on a
+// connection, one GATT procedure at a time. Reads are issued as read blob
+// requests and writes as queued prepare writes, both sized to the MTU.
+// Command responses carry no connection handle, so only one instance may have
+// a command in flight on an NCP at a time.
+#[derive(Debug)]
+pub struct LongValue {
+    connection: u8,
+    mtu: u16,
+    operation: Operation,
+    awaiting_response: bool,
+}
+
+impl LongValue {
+    pub fn new(connection: u8) -> LongValue {
+        LongValue {
+            connection,
+            mtu: DEFAULT_MTU,
+            operation: Operation::Idle,
+            awaiting_response: false,
+        }
+    }
+
+    pub fn connection(&self) -> u8 {
+        self.connection
+    }
+
+    pub fn mtu(&self) -> u16 {
+        self.mtu
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.operation != Operation::Idle
+    }
+
+    pub fn read_long(&mut self, characteristic: u16) -> Option<LongValueEvent> {
+        if self.is_busy() {
+            return None;
+        }
+        self.operation = Operation::Read {
+            characteristic,
+            offset: 0,
+            value: Vec::new(),
+        };
+        self.next()
+    }
+
+    pub fn write_long(&mut self, characteristic: u16, value: Vec<u8>) -> Option<LongValueEvent> {
+        self.write(characteristic, value, false)
+    }
+
+    // Like write_long, but every fragment echoed back by the server is
+    // compared with what was sent and the write is cancelled on a mismatch.
+    pub fn reliable_write(
+        &mut self,
+        characteristic: u16,
+        value: Vec<u8>,
+    ) -> Option<LongValueEvent> {
+        self.write(characteristic, value, true)
+    }
+
+    pub fn handle(&mut self, message: &Message) -> Option<LongValueEvent> {
+        match message.payload {
+            MessagePayload::evt_gatt_mtu_exchanged(ref x) if x.connection == self.connection => {
+                self.mtu = x.mtu;
+                None
+            }
+            MessagePayload::rsp_gatt_read_characteristic_value_from_offset(ref x) => {
+                self.response(x.result.clone())
+            }
+            MessagePayload::rsp_gatt_prepare_characteristic_value_write(ref x) => {
+                self.prepared(x.result.clone(), x.sent_len)
+            }
+            MessagePayload::rsp_gatt_prepare_characteristic_value_reliable_write(ref x) => {
+                self.prepared(x.result.clone(), x.sent_len)
+            }
+            MessagePayload::rsp_gatt_execute_characteristic_value_write(ref x) => {
+                self.response(x.result.clone())
+            }
+            MessagePayload::evt_gatt_characteristic_value(ref x)
+                if x.connection == self.connection =>
+            {
+                match self.operation {
+                    Operation::Read {
+                        characteristic,
+                        ref mut value,
+                        ..
+                    } if x.characteristic == characteristic => {
+                        // Fragments are placed by offset so a repeated one
+                        // replaces what was received before.
+                        value.truncate(x.offset as usize);
+                        value.extend_from_slice(&x.value);
+                    }
+                    Operation::Write {
+                        characteristic,
+                        ref value,
+                        reliable: true,
+                        ref mut mismatch,
+                        ..
+                    } if x.characteristic == characteristic
+                        && x.att_opcode == AttOpcode::prepare_write_response =>
+                    {
+                        let start = value.len().min(x.offset as usize);
+                        let end = value.len().min(start + x.value.len());
+                        if mismatch.is_none() && value[start..end] != x.value[..] {
+                            *mismatch = Some(Fragment {
+                                offset: x.offset,
+                                expected: value[start..end].to_vec(),
+                                received: x.value.clone(),
+                            });
+                        }
+                    }
+                    _ => (),
+                }
+                None
+            }
+            MessagePayload::evt_gatt_procedure_completed(ref x)
+                if x.connection == self.connection =>
+            {
+                self.completed(x.result.clone())
+            }
+            MessagePayload::evt_le_connection_closed(ref x) if x.connection == self.connection => {
+                if self.is_busy() {
+                    self.finish(LongValueEvent::Failed(x.reason.clone()))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    // Offsets are 16-bit, so longer values are rejected before anything
+    // is sent.
+    fn write(
+        &mut self,
+        characteristic: u16,
+        value: Vec<u8>,
+        reliable: bool,
+    ) -> Option<LongValueEvent> {
+        if self.is_busy() {
+            return None;
+        }
+        if value.len() > usize::from(u16::MAX) {
+            return Some(LongValueEvent::Failed(Error::invalid_att_length));
+        }
+        self.operation = Operation::Write {
+            characteristic,
+            offset: 0,
+            value,
+            reliable,
+            mismatch: None,
+        };
+        self.next()
+    }
+
+    fn response(&mut self, result: Error) -> Option<LongValueEvent> {
+        if !self.awaiting_response {
+            return None;
+        }
+        self.awaiting_response = false;
+        if result == Error::success {
+            None
+        } else {
+            self.fail(result)
+        }
+    }
+
+    fn prepared(&mut self, result: Error, sent_len: u16) -> Option<LongValueEvent> {
+        if self.awaiting_response && result == Error::success {
+            if let Operation::Write { ref mut offset, .. } = self.operation {
+                *offset += sent_len as usize;
+            }
+        }
+        self.response(result)
+    }
+
+    fn completed(&mut self, result: Error) -> Option<LongValueEvent> {
+        let operation = self.operation.clone();
+        match operation {
+            Operation::Read {
+                characteristic,
+                offset,
+                value,
+            } => match result {
+                Error::success if value.len().saturating_sub(offset) >= self.read_len() => {
+                    self.operation = Operation::Read {
+                        characteristic,
+                        offset: value.len(),
+                        value,
+                    };
+                    self.next()
+                }
+                Error::success => self.finish(LongValueEvent::ReadComplete(value)),
+                // A value whose length is a multiple of the fragment size
+                // ends with a read past its end.
+                Error::invalid_offset if offset > 0 && offset == value.len() => {
+                    self.finish(LongValueEvent::ReadComplete(value))
+                }
+                error => self.fail(error),
+            },
+            Operation::Write { mismatch, .. } => match (result, mismatch) {
+                (Error::success, Some(fragment)) => self.cancel(LongValueEvent::Mismatch(fragment)),
+                (Error::success, None) => self.next(),
+                (error, _) => self.fail(error),
+            },
+            Operation::Execute => match result {
+                Error::success => self.finish(LongValueEvent::WriteComplete),
+                error => self.finish(LongValueEvent::Failed(error)),
+            },
+            Operation::Cancel(event) => self.finish(event),
+            Operation::Idle => None,
+        }
+    }
+
+    fn fail(&mut self, error: Error) -> Option<LongValueEvent> {
+        match self.operation {
+            Operation::Write { .. } => self.cancel(LongValueEvent::Failed(error)),
+            Operation::Cancel(ref event) => {
+                let event = event.clone();
+                self.finish(event)
+            }
+            _ => self.finish(LongValueEvent::Failed(error)),
+        }
+    }
+
+    // A failed write leaves prepared fragments queued on the server, so they
+    // are cancelled before the failure is reported.
+    fn cancel(&mut self, event: LongValueEvent) -> Option<LongValueEvent> {
+        self.operation = Operation::Cancel(event);
+        self.awaiting_response = true;
+        Some(LongValueEvent::Command(
+            execute_characteristic_value_write::new(
+                self.connection,
+                execute_write_flag::cancel as u8,
+            ),
+        ))
+    }
+
+    fn finish(&mut self, event: LongValueEvent) -> Option<LongValueEvent> {
+        self.operation = Operation::Idle;
+        self.awaiting_response = false;
+        Some(event)
+    }
+
+    fn read_len(&self) -> usize {
+        (self.mtu - READ_HEADER) as usize
+    }
+
+    fn next(&mut self) -> Option<LongValueEvent> {
+        let command = match self.operation {
+            Operation::Read {
+                characteristic,
+                offset,
+                ..
+            } => read_characteristic_value_from_offset::new(
+                self.connection,
+                characteristic,
+                offset as u16,
+                self.read_len() as u16,
+            ),
+            Operation::Write {
+                characteristic,
+                offset,
+                ref value,
+                reliable,
+                ..
+            } if offset < value.len() => {
+                let len = (self.mtu - PREPARE_WRITE_HEADER) as usize;
+                let end = value.len().min(offset + len);
+                let fragment = value[offset..end].to_vec();
+                if reliable {
+                    prepare_characteristic_value_reliable_write::new(
+                        self.connection,
+                        characteristic,
+                        offset as u16,
+                        fragment,
+                    )
+                } else {
+                    prepare_characteristic_value_write::new(
+                        self.connection,
+                        characteristic,
+                        offset as u16,
+                        fragment,
+                    )
+                }
+            }
+            Operation::Write { .. } => {
+                self.operation = Operation::Execute;
+                execute_characteristic_value_write::new(
+                    self.connection,
+                    execute_write_flag::commit as u8,
+                )
+            }
+            _ => return None,
+        };
+        self.awaiting_response = true;
+        Some(LongValueEvent::Command(command))
+    }
+}
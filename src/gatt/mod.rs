@@ -1,10 +1,18 @@
+pub mod batch_read;
+pub mod cache;
 pub mod cmd;
+pub mod codec;
+pub mod descriptor;
+pub mod discovery;
 pub mod evt;
+pub mod long_value;
 pub mod rsp;
+pub mod subscription;
 
 use message::{MessageClass, MessageHeader, MessagePayload, MessageType};
 use num_derive::FromPrimitive;
 use std::io::{Error, ErrorKind};
+use std::ops::BitOr;
 
 pub fn parse(header: &MessageHeader, buffer: &[u8]) -> Result<MessagePayload, Error> {
     match header {
@@ -283,8 +291,35 @@ pub enum AttOpcode {
     handle_value_indication = 29,   // Indication
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct CharacteristicProperties(pub u8);
+
+impl CharacteristicProperties {
+    pub const BROADCAST: CharacteristicProperties = CharacteristicProperties(0x01);
+    pub const READ: CharacteristicProperties = CharacteristicProperties(0x02);
+    pub const WRITE_WITHOUT_RESPONSE: CharacteristicProperties = CharacteristicProperties(0x04);
+    pub const WRITE: CharacteristicProperties = CharacteristicProperties(0x08);
+    pub const NOTIFY: CharacteristicProperties = CharacteristicProperties(0x10);
+    pub const INDICATE: CharacteristicProperties = CharacteristicProperties(0x20);
+    pub const AUTHENTICATED_SIGNED_WRITES: CharacteristicProperties =
+        CharacteristicProperties(0x40);
+    pub const EXTENDED_PROPERTIES: CharacteristicProperties = CharacteristicProperties(0x80);
+
+    pub fn contains(&self, other: CharacteristicProperties) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for CharacteristicProperties {
+    type Output = CharacteristicProperties;
+
+    fn bitor(self, other: CharacteristicProperties) -> CharacteristicProperties {
+        CharacteristicProperties(self.0 | other.0)
+    }
+}
+
 #[allow(non_camel_case_types)]
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ClientConfigFlag {
     disable = 0,      // Disable notifications and indications
     notification = 1, // Notification
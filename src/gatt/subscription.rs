@@ -0,0 +1,163 @@
+use gatt::cmd::{send_characteristic_confirmation, set_characteristic_notification};
+use gatt::{AttOpcode, ClientConfigFlag};
+use message::{Message, MessagePayload};
+use std::collections::HashMap;
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+const NO_BONDING: u8 = 0xff;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub connection: u8,
+    pub characteristic: u16,
+    pub indication: bool,
+    pub value: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct Subscription {
+    flag: ClientConfigFlag,
+    sender: Sender<Notification>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Peer {
+    address: [u8; 6],
+    bonded: bool,
+}
+
+// Routes notifications and indications to one channel per subscribed
+// characteristic. Subscriptions are kept per peer address, so those of a
+// bonded peer survive a disconnection and are restored when it reconnects.
+#[derive(Debug, Default)]
+pub struct SubscriptionManager {
+    subscriptions: HashMap<([u8; 6], u16), Subscription>,
+    connections: HashMap<u8, Peer>,
+}
+
+impl SubscriptionManager {
+    pub fn new() -> SubscriptionManager {
+        SubscriptionManager::default()
+    }
+
+    // Returns the receiving end of the subscription and the command enabling
+    // it on the peer, or None if the connection is not known.
+    pub fn subscribe(
+        &mut self,
+        connection: u8,
+        characteristic: u16,
+        flag: ClientConfigFlag,
+    ) -> Option<(Receiver<Notification>, Message)> {
+        let address = self.connections.get(&connection)?.address;
+        let (sender, receiver) = channel();
+        let command =
+            set_characteristic_notification::new(connection, characteristic, flag.clone() as u8);
+        self.subscriptions
+            .insert((address, characteristic), Subscription { flag, sender });
+        Some((receiver, command))
+    }
+
+    pub fn unsubscribe(&mut self, connection: u8, characteristic: u16) -> Option<Message> {
+        let address = self.connections.get(&connection)?.address;
+        self.subscriptions.remove(&(address, characteristic))?;
+        Some(disable(connection, characteristic))
+    }
+
+    pub fn is_subscribed(&self, connection: u8, characteristic: u16) -> bool {
+        match self.connections.get(&connection) {
+            Some(peer) => self
+                .subscriptions
+                .contains_key(&(peer.address, characteristic)),
+            None => false,
+        }
+    }
+
+    pub fn handle(&mut self, message: &Message) -> Vec<Message> {
+        match message.payload {
+            MessagePayload::evt_le_connection_opened(ref x) => {
+                let bonded = x.bonding != NO_BONDING;
+                self.connections.insert(
+                    x.connection,
+                    Peer {
+                        address: x.address,
+                        bonded,
+                    },
+                );
+                if !bonded {
+                    return Vec::new();
+                }
+                self.subscriptions
+                    .iter()
+                    .filter(|(key, _)| key.0 == x.address)
+                    .map(|(key, subscription)| {
+                        set_characteristic_notification::new(
+                            x.connection,
+                            key.1,
+                            subscription.flag.clone() as u8,
+                        )
+                    })
+                    .collect()
+            }
+            MessagePayload::evt_le_connection_closed(ref x) => {
+                if let Some(peer) = self.connections.remove(&x.connection) {
+                    if !peer.bonded {
+                        self.subscriptions.retain(|key, _| key.0 != peer.address);
+                    }
+                }
+                Vec::new()
+            }
+            // Subscriptions made before bonding are kept for the next connection.
+            MessagePayload::evt_sm_bonded(ref x) if x.bonding != NO_BONDING => {
+                if let Some(peer) = self.connections.get_mut(&x.connection) {
+                    peer.bonded = true;
+                }
+                Vec::new()
+            }
+            MessagePayload::evt_gatt_characteristic_value(ref x) => {
+                let indication = match x.att_opcode {
+                    AttOpcode::handle_value_notification => false,
+                    AttOpcode::handle_value_indication => true,
+                    _ => return Vec::new(),
+                };
+                let mut commands = Vec::new();
+                // Indications are confirmed even when nobody listens, as the
+                // peer sends nothing else until it gets the confirmation.
+                if indication {
+                    commands.push(send_characteristic_confirmation::new(x.connection));
+                }
+                let address = match self.connections.get(&x.connection) {
+                    Some(peer) => peer.address,
+                    None => return commands,
+                };
+                let key = (address, x.characteristic);
+                let closed = match self.subscriptions.get(&key) {
+                    Some(subscription) => subscription
+                        .sender
+                        .send(Notification {
+                            connection: x.connection,
+                            characteristic: x.characteristic,
+                            indication,
+                            value: x.value.clone(),
+                        })
+                        .is_err(),
+                    None => false,
+                };
+                // The receiver was dropped, so the subscription is no longer wanted.
+                if closed {
+                    self.subscriptions.remove(&key);
+                    commands.push(disable(x.connection, x.characteristic));
+                }
+                commands
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn disable(connection: u8, characteristic: u16) -> Message {
+    set_characteristic_notification::new(
+        connection,
+        characteristic,
+        ClientConfigFlag::disable as u8,
+    )
+}
@@ -0,0 +1,315 @@
+use gatt::CharacteristicProperties;
+use hex::to_hex;
+use std::fmt::Write;
+
+pub const PRIMARY_SERVICE: [u8; 2] = [0x28, 0x00];
+pub const SECONDARY_SERVICE: [u8; 2] = [0x28, 0x01];
+pub const INCLUDE: [u8; 2] = [0x28, 0x02];
+pub const CHARACTERISTIC: [u8; 2] = [0x28, 0x03];
+pub const CLIENT_CONFIGURATION: [u8; 2] = [0x29, 0x02];
+
+// An attribute of the local database. The type is a UUID with its most
+// significant byte first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute {
+    pub handle: u16,
+    pub atype: Vec<u8>,
+    pub value: Vec<u8>,
+}
+
+// How a value is declared in gatt.xml. Values read from the stack are hex.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValueType {
+    Hex,
+    Utf8,
+    Constant,    // Read-only, stored in flash
+    User(usize), // Maximum length, served through user_*_request events
+}
+
+// The id, writable and value type fields are only known for databases built
+// from a declaration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Descriptor {
+    pub handle: u16,
+    pub id: Option<String>,
+    pub uuid: Vec<u8>,
+    pub writable: bool,
+    pub value_type: ValueType,
+    pub value: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Characteristic {
+    pub handle: u16, // Declaration handle
+    pub value_handle: u16,
+    pub id: Option<String>,
+    pub uuid: Vec<u8>,
+    pub properties: CharacteristicProperties,
+    pub value_type: ValueType,
+    pub value: Vec<u8>,
+    pub descriptors: Vec<Descriptor>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Service {
+    pub handle: u16,
+    pub id: Option<String>,
+    pub uuid: Vec<u8>,
+    pub primary: bool,
+    pub advertise: bool,
+    pub includes: Vec<u16>, // Handles of included services
+    pub characteristics: Vec<Characteristic>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Database {
+    pub services: Vec<Service>,
+}
+
+impl Database {
+    // Rebuilds the structure of a database from its attributes in handle
+    // order. Attributes before the first service are ignored.
+    pub fn from_attributes(attributes: &[Attribute]) -> Database {
+        let mut services: Vec<Service> = Vec::new();
+        for attribute in attributes {
+            let atype = attribute.atype.as_slice();
+            if atype == PRIMARY_SERVICE || atype == SECONDARY_SERVICE {
+                services.push(Service {
+                    handle: attribute.handle,
+                    id: None,
+                    uuid: reversed(&attribute.value),
+                    primary: atype == PRIMARY_SERVICE,
+                    advertise: false,
+                    includes: Vec::new(),
+                    characteristics: Vec::new(),
+                });
+                continue;
+            }
+            let service = match services.last_mut() {
+                Some(service) => service,
+                None => continue,
+            };
+            if atype == INCLUDE && attribute.value.len() >= 2 {
+                service
+                    .includes
+                    .push(u16::from(attribute.value[0]) | u16::from(attribute.value[1]) << 8);
+            } else if atype == CHARACTERISTIC && attribute.value.len() >= 5 {
+                let value = &attribute.value;
+                service.characteristics.push(Characteristic {
+                    handle: attribute.handle,
+                    value_handle: u16::from(value[1]) | u16::from(value[2]) << 8,
+                    id: None,
+                    uuid: reversed(&value[3..]),
+                    properties: CharacteristicProperties(value[0]),
+                    value_type: ValueType::Hex,
+                    value: Vec::new(),
+                    descriptors: Vec::new(),
+                });
+            } else if let Some(characteristic) = service.characteristics.last_mut() {
+                if attribute.handle == characteristic.value_handle {
+                    characteristic.value = attribute.value.clone();
+                } else {
+                    characteristic.descriptors.push(Descriptor {
+                        handle: attribute.handle,
+                        id: None,
+                        uuid: attribute.atype.clone(),
+                        writable: false,
+                        value_type: ValueType::Hex,
+                        value: attribute.value.clone(),
+                    });
+                }
+            }
+        }
+        Database { services }
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{\"services\":[");
+        for (i, service) in self.services.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            let _ = write!(
+                json,
+                "{{\"handle\":{},\"uuid\":\"{}\",\"primary\":{},\"includes\":{:?},\"characteristics\":[",
+                service.handle,
+                to_hex(&service.uuid),
+                service.primary,
+                service.includes
+            );
+            for (j, characteristic) in service.characteristics.iter().enumerate() {
+                if j > 0 {
+                    json.push(',');
+                }
+                let _ = write!(
+                    json,
+                    "{{\"handle\":{},\"value_handle\":{},\"uuid\":\"{}\",\"properties\":{:?},\"value\":\"{}\",\"descriptors\":[",
+                    characteristic.handle,
+                    characteristic.value_handle,
+                    to_hex(&characteristic.uuid),
+                    property_names(characteristic.properties),
+                    to_hex(&characteristic.value)
+                );
+                for (k, descriptor) in characteristic.descriptors.iter().enumerate() {
+                    if k > 0 {
+                        json.push(',');
+                    }
+                    let _ = write!(
+                        json,
+                        "{{\"handle\":{},\"uuid\":\"{}\",\"value\":\"{}\"}}",
+                        descriptor.handle,
+                        to_hex(&descriptor.uuid),
+                        to_hex(&descriptor.value)
+                    );
+                }
+                json.push_str("]}");
+            }
+            json.push_str("]}");
+        }
+        json.push_str("]}");
+        json
+    }
+
+    // Silicon Labs GATT configurator format. Client characteristic
+    // configuration descriptors are left out as the GATT compiler adds them
+    // to characteristics that notify or indicate.
+    pub fn to_gatt_xml(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<gatt>\n");
+        for service in &self.services {
+            let _ = writeln!(
+                xml,
+                "  <service uuid=\"{}\"{} type=\"{}\"{}>",
+                to_hex(&service.uuid),
+                id_attribute(&service.id),
+                if service.primary {
+                    "primary"
+                } else {
+                    "secondary"
+                },
+                if service.advertise {
+                    " advertise=\"true\""
+                } else {
+                    ""
+                }
+            );
+            for characteristic in &service.characteristics {
+                let _ = writeln!(
+                    xml,
+                    "    <characteristic uuid=\"{}\"{}>",
+                    to_hex(&characteristic.uuid),
+                    id_attribute(&characteristic.id)
+                );
+                let mut properties = property_names(characteristic.properties);
+                if characteristic.value_type == ValueType::Constant {
+                    properties.push("const");
+                }
+                write_properties(&mut xml, "      ", &properties);
+                write_value(
+                    &mut xml,
+                    "      ",
+                    characteristic.value_type,
+                    &characteristic.value,
+                );
+                for descriptor in &characteristic.descriptors {
+                    if descriptor.uuid == CLIENT_CONFIGURATION {
+                        continue;
+                    }
+                    let _ = writeln!(
+                        xml,
+                        "      <descriptor uuid=\"{}\"{}>",
+                        to_hex(&descriptor.uuid),
+                        id_attribute(&descriptor.id)
+                    );
+                    let mut properties = vec!["read"];
+                    if descriptor.writable {
+                        properties.push("write");
+                    }
+                    if descriptor.value_type == ValueType::Constant {
+                        properties.push("const");
+                    }
+                    write_properties(&mut xml, "        ", &properties);
+                    write_value(
+                        &mut xml,
+                        "        ",
+                        descriptor.value_type,
+                        &descriptor.value,
+                    );
+                    xml.push_str("      </descriptor>\n");
+                }
+                xml.push_str("    </characteristic>\n");
+            }
+            xml.push_str("  </service>\n");
+        }
+        xml.push_str("</gatt>\n");
+        xml
+    }
+}
+
+fn id_attribute(id: &Option<String>) -> String {
+    match *id {
+        Some(ref x) => format!(" id=\"{}\"", escape(x)),
+        None => String::new(),
+    }
+}
+
+fn write_properties(xml: &mut String, indent: &str, properties: &[&str]) {
+    let attributes: Vec<String> = properties
+        .iter()
+        .map(|x| format!(" {}=\"true\"", x))
+        .collect();
+    let _ = writeln!(xml, "{}<properties{}/>", indent, attributes.concat());
+}
+
+fn write_value(xml: &mut String, indent: &str, value_type: ValueType, value: &[u8]) {
+    let _ = match value_type {
+        ValueType::Hex | ValueType::Constant => writeln!(
+            xml,
+            "{}<value type=\"hex\" length=\"{}\">{}</value>",
+            indent,
+            value.len(),
+            to_hex(value)
+        ),
+        ValueType::Utf8 => writeln!(
+            xml,
+            "{}<value type=\"utf-8\" length=\"{}\">{}</value>",
+            indent,
+            value.len(),
+            escape(&String::from_utf8_lossy(value))
+        ),
+        ValueType::User(length) => writeln!(
+            xml,
+            "{}<value type=\"user\" length=\"{}\"/>",
+            indent, length
+        ),
+    };
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Property names as used by the GATT configurator.
+pub fn property_names(properties: CharacteristicProperties) -> Vec<&'static str> {
+    [
+        (CharacteristicProperties::BROADCAST, "broadcast"),
+        (CharacteristicProperties::READ, "read"),
+        (
+            CharacteristicProperties::WRITE_WITHOUT_RESPONSE,
+            "write_no_response",
+        ),
+        (CharacteristicProperties::WRITE, "write"),
+        (CharacteristicProperties::NOTIFY, "notify"),
+        (CharacteristicProperties::INDICATE, "indicate"),
+    ]
+    .iter()
+    .filter(|x| properties.contains(x.0))
+    .map(|x| x.1)
+    .collect()
+}
+
+fn reversed(data: &[u8]) -> Vec<u8> {
+    data.iter().rev().cloned().collect()
+}
@@ -0,0 +1,249 @@
+use gatt::CharacteristicProperties;
+use gatt_server::cmd::write_attribute_value;
+use gatt_server::database::{
+    Characteristic, Database, Descriptor, Service, ValueType, CLIENT_CONFIGURATION,
+};
+use message::Message;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Constant(Vec<u8>), // Read-only, stored in flash
+    Hex(Vec<u8>),      // Initial value, stored by the stack
+    Utf8(String),      // Initial value, stored by the stack
+    User(usize),       // Maximum length, served through user_*_request events
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DescriptorSpec {
+    pub id: Option<String>,
+    pub uuid: Vec<u8>, // Most significant byte first
+    pub writable: bool,
+    pub value: Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CharacteristicSpec {
+    pub id: String,
+    pub uuid: Vec<u8>, // Most significant byte first
+    pub properties: CharacteristicProperties,
+    pub value: Value,
+    pub descriptors: Vec<DescriptorSpec>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceSpec {
+    pub id: String,
+    pub uuid: Vec<u8>, // Most significant byte first
+    pub primary: bool,
+    pub advertise: bool,
+    pub characteristics: Vec<CharacteristicSpec>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DatabaseSpec {
+    pub services: Vec<ServiceSpec>,
+}
+
+impl DescriptorSpec {
+    pub fn new(uuid: &[u8], value: Value) -> DescriptorSpec {
+        DescriptorSpec {
+            id: None,
+            uuid: uuid.to_vec(),
+            writable: false,
+            value,
+        }
+    }
+}
+
+impl CharacteristicSpec {
+    pub fn new(
+        id: &str,
+        uuid: &[u8],
+        properties: CharacteristicProperties,
+        value: Value,
+    ) -> CharacteristicSpec {
+        CharacteristicSpec {
+            id: id.to_string(),
+            uuid: uuid.to_vec(),
+            properties,
+            value,
+            descriptors: Vec::new(),
+        }
+    }
+
+    pub fn descriptor(mut self, descriptor: DescriptorSpec) -> CharacteristicSpec {
+        self.descriptors.push(descriptor);
+        self
+    }
+
+    // The GATT compiler adds a client characteristic configuration
+    // descriptor to characteristics that notify or indicate.
+    fn has_client_configuration(&self) -> bool {
+        self.properties.contains(CharacteristicProperties::NOTIFY)
+            || self.properties.contains(CharacteristicProperties::INDICATE)
+    }
+}
+
+impl ServiceSpec {
+    pub fn new(id: &str, uuid: &[u8]) -> ServiceSpec {
+        ServiceSpec {
+            id: id.to_string(),
+            uuid: uuid.to_vec(),
+            primary: true,
+            advertise: false,
+            characteristics: Vec::new(),
+        }
+    }
+
+    pub fn characteristic(mut self, characteristic: CharacteristicSpec) -> ServiceSpec {
+        self.characteristics.push(characteristic);
+        self
+    }
+}
+
+impl DatabaseSpec {
+    pub fn new() -> DatabaseSpec {
+        DatabaseSpec::default()
+    }
+
+    pub fn service(mut self, service: ServiceSpec) -> DatabaseSpec {
+        self.services.push(service);
+        self
+    }
+
+    // The database as built by the GATT compiler when this is the whole
+    // database: each declaration is followed by the characteristic value,
+    // the client characteristic configuration and then the descriptors.
+    pub fn to_database(&self) -> Database {
+        let mut handle = 0;
+        let mut services = Vec::new();
+        for service in &self.services {
+            handle += 1;
+            let mut characteristics = Vec::new();
+            let service_handle = handle;
+            for characteristic in &service.characteristics {
+                let (value_type, value) = declared(&characteristic.value);
+                let mut declared_characteristic = Characteristic {
+                    handle: handle + 1,
+                    value_handle: handle + 2,
+                    id: Some(characteristic.id.clone()),
+                    uuid: characteristic.uuid.clone(),
+                    properties: characteristic.properties,
+                    value_type,
+                    value,
+                    descriptors: Vec::new(),
+                };
+                handle += 2;
+                if characteristic.has_client_configuration() {
+                    handle += 1;
+                    declared_characteristic.descriptors.push(Descriptor {
+                        handle,
+                        id: None,
+                        uuid: CLIENT_CONFIGURATION.to_vec(),
+                        writable: true,
+                        value_type: ValueType::Hex,
+                        value: vec![0, 0],
+                    });
+                }
+                for descriptor in &characteristic.descriptors {
+                    if descriptor.uuid == CLIENT_CONFIGURATION {
+                        continue;
+                    }
+                    handle += 1;
+                    let (value_type, value) = declared(&descriptor.value);
+                    declared_characteristic.descriptors.push(Descriptor {
+                        handle,
+                        id: descriptor.id.clone(),
+                        uuid: descriptor.uuid.clone(),
+                        writable: descriptor.writable,
+                        value_type,
+                        value,
+                    });
+                }
+                characteristics.push(declared_characteristic);
+            }
+            services.push(Service {
+                handle: service_handle,
+                id: Some(service.id.clone()),
+                uuid: service.uuid.clone(),
+                primary: service.primary,
+                advertise: service.advertise,
+                includes: Vec::new(),
+                characteristics,
+            });
+        }
+        Database { services }
+    }
+
+    pub fn handles(&self) -> HandleMap {
+        let mut map = HandleMap::default();
+        for service in self.to_database().services {
+            if let Some(ref id) = service.id {
+                map.insert(id, service.handle, false);
+            }
+            for characteristic in &service.characteristics {
+                if let Some(ref id) = characteristic.id {
+                    let user = matches!(characteristic.value_type, ValueType::User(_));
+                    map.insert(id, characteristic.value_handle, user);
+                }
+                for descriptor in &characteristic.descriptors {
+                    if let Some(ref id) = descriptor.id {
+                        let user = matches!(descriptor.value_type, ValueType::User(_));
+                        map.insert(id, descriptor.handle, user);
+                    }
+                }
+            }
+        }
+        map
+    }
+
+    pub fn to_gatt_xml(&self) -> String {
+        self.to_database().to_gatt_xml()
+    }
+}
+
+fn declared(value: &Value) -> (ValueType, Vec<u8>) {
+    match *value {
+        Value::Constant(ref x) => (ValueType::Constant, x.clone()),
+        Value::Hex(ref x) => (ValueType::Hex, x.clone()),
+        Value::Utf8(ref x) => (ValueType::Utf8, x.as_bytes().to_vec()),
+        Value::User(length) => (ValueType::User(length), Vec::new()),
+    }
+}
+
+// Attribute handles by the ids given in the declaration.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HandleMap {
+    handles: BTreeMap<String, u16>,
+    ids: BTreeMap<u16, String>,
+    user: Vec<u16>,
+}
+
+impl HandleMap {
+    fn insert(&mut self, id: &str, handle: u16, user: bool) {
+        self.handles.insert(id.to_string(), handle);
+        self.ids.insert(handle, id.to_string());
+        if user {
+            self.user.push(handle);
+        }
+    }
+
+    // Handle of a service declaration, characteristic value or descriptor.
+    pub fn handle(&self, id: &str) -> Option<u16> {
+        self.handles.get(id).cloned()
+    }
+
+    // Id of the attribute in a user_read_request or user_write_request.
+    pub fn id(&self, handle: u16) -> Option<&str> {
+        self.ids.get(&handle).map(|x| x.as_str())
+    }
+
+    pub fn is_user(&self, handle: u16) -> bool {
+        self.user.contains(&handle)
+    }
+
+    pub fn write(&self, id: &str, value: Vec<u8>) -> Option<Message> {
+        Some(write_attribute_value::new(self.handle(id)?, 0, value))
+    }
+}
@@ -0,0 +1,111 @@
+use error::Error;
+use gatt_server::cmd::{find_attribute, read_attribute_type, read_attribute_value};
+use gatt_server::database::{Attribute, Database, PRIMARY_SERVICE};
+use message::{Message, MessagePayload};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DumpEvent {
+    Command(Message), // Next command to send
+    Complete(Database),
+    Failed(Error),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Idle,
+    Find,
+    Type(u16),
+    Value(u16),
+    Done,
+}
+
+// Reads back the local GATT database: finds the first service, then reads
+// the type and value of each attribute in turn until a handle is not valid.
+#[derive(Debug)]
+pub struct Dump {
+    attributes: Vec<Attribute>,
+    step: Step,
+}
+
+impl Default for Dump {
+    fn default() -> Dump {
+        Dump::new()
+    }
+}
+
+impl Dump {
+    pub fn new() -> Dump {
+        Dump {
+            attributes: Vec::new(),
+            step: Step::Idle,
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.step == Step::Done
+    }
+
+    pub fn start(&mut self) -> DumpEvent {
+        self.attributes.clear();
+        self.step = Step::Find;
+        let mut atype = PRIMARY_SERVICE.to_vec();
+        atype.reverse();
+        DumpEvent::Command(find_attribute::new(1, atype))
+    }
+
+    pub fn handle(&mut self, message: &Message) -> Option<DumpEvent> {
+        match (self.step.clone(), &message.payload) {
+            (Step::Find, MessagePayload::rsp_gatt_server_find_attribute(x)) => match x.result {
+                Error::success => Some(self.read_type(x.attribute)),
+                Error::att_not_found | Error::invalid_handle => self.complete(),
+                ref error => {
+                    self.step = Step::Done;
+                    Some(DumpEvent::Failed(error.clone()))
+                }
+            },
+            (Step::Type(handle), MessagePayload::rsp_gatt_server_read_attribute_type(x)) => {
+                if x.result != Error::success {
+                    // Past the last attribute.
+                    return self.complete();
+                }
+                self.attributes.push(Attribute {
+                    handle,
+                    atype: x.atype.iter().rev().cloned().collect(),
+                    value: Vec::new(),
+                });
+                self.step = Step::Value(handle);
+                Some(DumpEvent::Command(read_attribute_value::new(handle, 0)))
+            }
+            (Step::Value(handle), MessagePayload::rsp_gatt_server_read_attribute_value(x)) => {
+                let attribute = self.attributes.last_mut()?;
+                // Values longer than one response are read in parts until
+                // nothing is left. Unreadable values, such as those of user
+                // characteristics, are left empty.
+                if x.result == Error::success && !x.value.is_empty() {
+                    attribute.value.extend_from_slice(&x.value);
+                    let offset = attribute.value.len() as u16;
+                    return Some(DumpEvent::Command(read_attribute_value::new(
+                        handle, offset,
+                    )));
+                }
+                match handle.checked_add(1) {
+                    Some(next) => Some(self.read_type(next)),
+                    None => self.complete(),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn read_type(&mut self, handle: u16) -> DumpEvent {
+        self.step = Step::Type(handle);
+        DumpEvent::Command(read_attribute_type::new(handle))
+    }
+
+    fn complete(&mut self) -> Option<DumpEvent> {
+        self.step = Step::Done;
+        Some(DumpEvent::Complete(Database::from_attributes(
+            &self.attributes,
+        )))
+    }
+}
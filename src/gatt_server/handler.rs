@@ -0,0 +1,183 @@
+use error::Error;
+use gatt::AttOpcode;
+use gatt_server::cmd::{send_user_read_response, send_user_write_response};
+use gatt_server::AttErrorCode;
+use message::{Message, MessagePayload};
+use std::collections::HashMap;
+
+// Value of a user type characteristic, served by the application.
+pub trait AttributeHandler {
+    // Value starting at the given offset. read_at can be used to slice a
+    // complete value.
+    fn read(&mut self, _connection: u8, _offset: u16) -> Result<Vec<u8>, AttErrorCode> {
+        Err(AttErrorCode::read_not_permitted)
+    }
+
+    fn write(&mut self, _connection: u8, _offset: u16, _value: &[u8]) -> Result<(), AttErrorCode> {
+        Err(AttErrorCode::write_not_permitted)
+    }
+
+    // Fragments queued with prepare write requests, as (offset, value), once
+    // the client executes them. By default each fragment is written in turn,
+    // stopping at the first error.
+    fn execute_write(
+        &mut self,
+        connection: u8,
+        fragments: &[(u16, Vec<u8>)],
+    ) -> Result<(), AttErrorCode> {
+        for (offset, value) in fragments {
+            self.write(connection, *offset, value)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn read_at(value: &[u8], offset: u16) -> Result<Vec<u8>, AttErrorCode> {
+    match value.get(offset as usize..) {
+        Some(x) => Ok(x.to_vec()),
+        None => Err(AttErrorCode::invalid_offset),
+    }
+}
+
+// Dispatches user read and write requests to the handler registered for the
+// characteristic and builds the response, so it can be sent right away well
+// within the ATT transaction timeout.
+#[derive(Default)]
+pub struct HandlerRegistry {
+    handlers: HashMap<u16, Box<dyn AttributeHandler>>,
+    prepared: HashMap<u8, Vec<(u16, u16, Vec<u8>)>>, // Queued writes by connection
+}
+
+impl HandlerRegistry {
+    pub fn new() -> HandlerRegistry {
+        HandlerRegistry::default()
+    }
+
+    pub fn register(&mut self, characteristic: u16, handler: Box<dyn AttributeHandler>) {
+        self.handlers.insert(characteristic, handler);
+    }
+
+    pub fn unregister(&mut self, characteristic: u16) -> Option<Box<dyn AttributeHandler>> {
+        self.handlers.remove(&characteristic)
+    }
+
+    pub fn is_registered(&self, characteristic: u16) -> bool {
+        self.handlers.contains_key(&characteristic)
+    }
+
+    // Returns the response to send, if the message is a request that needs one.
+    pub fn handle(&mut self, message: &Message) -> Option<Message> {
+        match message.payload {
+            MessagePayload::evt_gatt_server_user_read_request(ref x) => {
+                let result = match self.handlers.get_mut(&x.characteristic) {
+                    Some(handler) => handler.read(x.connection, x.offset),
+                    None => Err(AttErrorCode::read_not_permitted),
+                };
+                let (code, value) = match result {
+                    Ok(value) => (AttErrorCode::success, value),
+                    Err(code) => (code, Vec::new()),
+                };
+                Some(send_user_read_response::new(
+                    x.connection,
+                    x.characteristic,
+                    code as u8,
+                    value,
+                ))
+            }
+            // The flags of an execute write request are 0 to cancel the
+            // queued writes and 1 to write them.
+            MessagePayload::evt_gatt_server_user_write_request(ref x)
+                if x.att_opcode == AttOpcode::execute_write_request as u8 =>
+            {
+                let code = match x.value.first() {
+                    Some(0) => {
+                        self.prepared.remove(&x.connection);
+                        AttErrorCode::success
+                    }
+                    _ => self.execute(x.connection),
+                };
+                Some(send_user_write_response::new(
+                    x.connection,
+                    x.characteristic,
+                    code as u8,
+                ))
+            }
+            MessagePayload::evt_gatt_server_user_write_request(ref x) => {
+                let code = if x.att_opcode == AttOpcode::prepare_write_request as u8 {
+                    if self.handlers.contains_key(&x.characteristic) {
+                        self.prepared.entry(x.connection).or_default().push((
+                            x.characteristic,
+                            x.offset,
+                            x.value.clone(),
+                        ));
+                        AttErrorCode::success
+                    } else {
+                        AttErrorCode::write_not_permitted
+                    }
+                } else {
+                    let result = match self.handlers.get_mut(&x.characteristic) {
+                        Some(handler) => handler.write(x.connection, x.offset, &x.value),
+                        None => Err(AttErrorCode::write_not_permitted),
+                    };
+                    match result {
+                        Ok(()) => AttErrorCode::success,
+                        Err(code) => code,
+                    }
+                };
+                // Write commands are not acknowledged.
+                if x.att_opcode == AttOpcode::write_command as u8 {
+                    return None;
+                }
+                Some(send_user_write_response::new(
+                    x.connection,
+                    x.characteristic,
+                    code as u8,
+                ))
+            }
+            // Writes still queued were executed without a request being
+            // reported, and the stack has already answered the client.
+            MessagePayload::evt_gatt_server_execute_write_completed(ref x) => {
+                if x.result == Error::success {
+                    self.execute(x.connection);
+                } else {
+                    self.prepared.remove(&x.connection);
+                }
+                None
+            }
+            MessagePayload::evt_le_connection_closed(ref x) => {
+                self.prepared.remove(&x.connection);
+                None
+            }
+            _ => None,
+        }
+    }
+    // Executes the queued writes of a connection, characteristic by
+    // characteristic, and returns the first error.
+    fn execute(&mut self, connection: u8) -> AttErrorCode {
+        let queued = match self.prepared.remove(&connection) {
+            Some(x) => x,
+            None => return AttErrorCode::success,
+        };
+        let mut characteristics: Vec<u16> = Vec::new();
+        for fragment in &queued {
+            if !characteristics.contains(&fragment.0) {
+                characteristics.push(fragment.0);
+            }
+        }
+        for characteristic in characteristics {
+            let fragments: Vec<(u16, Vec<u8>)> = queued
+                .iter()
+                .filter(|x| x.0 == characteristic)
+                .map(|x| (x.1, x.2.clone()))
+                .collect();
+            let result = match self.handlers.get_mut(&characteristic) {
+                Some(handler) => handler.execute_write(connection, &fragments),
+                None => Err(AttErrorCode::write_not_permitted),
+            };
+            if let Err(code) = result {
+                return code;
+            }
+        }
+        AttErrorCode::success
+    }
+}
@@ -1,8 +1,14 @@
 pub mod cmd;
+pub mod database;
+pub mod declaration;
+pub mod dump;
 pub mod evt;
+pub mod handler;
+pub mod notifier;
 pub mod rsp;
 
 use message::{MessageClass, MessageHeader, MessagePayload, MessageType};
+use num_derive::FromPrimitive;
 use std::io::{Error, ErrorKind};
 
 pub fn parse(header: &MessageHeader, buffer: &[u8]) -> Result<MessagePayload, Error> {
@@ -136,3 +142,31 @@ pub enum CharacteristicStatusFlag {
     client_config = 1, // Characteristic client configuration has been changed.
     confirmation = 2,  // Characteristic confirmation has been received.
 }
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, FromPrimitive)]
+pub enum AttErrorCode {
+    success = 0x00,                          // No error
+    invalid_handle = 0x01,                   // The attribute handle given was not valid
+    read_not_permitted = 0x02,               // The attribute cannot be read
+    write_not_permitted = 0x03,              // The attribute cannot be written
+    invalid_pdu = 0x04,                      // The attribute PDU was invalid
+    insufficient_authentication = 0x05,      // The attribute requires authentication
+    request_not_supported = 0x06,            // The server does not support the request
+    invalid_offset = 0x07,                   // Offset specified was past the end of the attribute
+    insufficient_authorization = 0x08,       // The attribute requires authorization
+    prepare_queue_full = 0x09,               // Too many prepare writes have been queued
+    attribute_not_found = 0x0a,              // No attribute found within the handle range
+    attribute_not_long = 0x0b,               // The attribute cannot be read or written using blobs
+    insufficient_encryption_key_size = 0x0c, // The encryption key size is insufficient
+    invalid_attribute_value_length = 0x0d,   // The attribute value length is invalid
+    unlikely_error = 0x0e,                   // The request encountered an unlikely error
+    insufficient_encryption = 0x0f,          // The attribute requires encryption
+    unsupported_group_type = 0x10,           // The attribute type is not a supported grouping
+    insufficient_resources = 0x11,           // Insufficient resources to complete the request
+    application = 0x80,                      // Application error
+    write_request_rejected = 0xfc,           // Write request rejected
+    cccd_improperly_configured = 0xfd, // Client characteristic configuration descriptor improperly configured
+    procedure_already_in_progress = 0xfe, // Procedure already in progress
+    out_of_range = 0xff,               // Attribute value out of range
+}
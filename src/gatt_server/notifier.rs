@@ -0,0 +1,131 @@
+use error::Error;
+use gatt::ClientConfigFlag;
+use gatt_server::cmd::send_characteristic_notification;
+use gatt_server::CharacteristicStatusFlag;
+use message::{Message, MessagePayload};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+// Tracks which clients enabled notifications or indications and sends values
+// only to them. The stack sends an indication instead of a notification when
+// the client enabled indications, and a client confirms one indication before
+// the next, so indications are queued per connection.
+#[derive(Debug, Default)]
+pub struct Notifier {
+    subscriptions: BTreeMap<(u8, u16), u16>, // Client config flags by connection and characteristic
+    queues: HashMap<u8, VecDeque<(u16, Vec<u8>)>>,
+    in_flight: HashSet<u8>,
+    pending: VecDeque<Option<u8>>, // Connection of each sent indication, None for notifications
+}
+
+impl Notifier {
+    pub fn new() -> Notifier {
+        Notifier::default()
+    }
+
+    pub fn client_config(&self, connection: u8, characteristic: u16) -> u16 {
+        *self
+            .subscriptions
+            .get(&(connection, characteristic))
+            .unwrap_or(&0)
+    }
+
+    pub fn subscribers(&self, characteristic: u16) -> Vec<u8> {
+        self.subscriptions
+            .keys()
+            .filter(|x| x.1 == characteristic)
+            .map(|x| x.0)
+            .collect()
+    }
+
+    // Indications waiting for earlier ones to be confirmed.
+    pub fn queued(&self, connection: u8) -> usize {
+        self.queues.get(&connection).map_or(0, |x| x.len())
+    }
+
+    pub fn notify_all(&mut self, characteristic: u16, value: &[u8]) -> Vec<Message> {
+        self.subscribers(characteristic)
+            .into_iter()
+            .filter_map(|connection| self.notify(connection, characteristic, value))
+            .collect()
+    }
+
+    // Returns the command to send now, or None if the client is not
+    // subscribed or the indication was queued.
+    pub fn notify(&mut self, connection: u8, characteristic: u16, value: &[u8]) -> Option<Message> {
+        let flags = self.client_config(connection, characteristic);
+        if flags & ClientConfigFlag::indication as u16 != 0 {
+            self.queues
+                .entry(connection)
+                .or_default()
+                .push_back((characteristic, value.to_vec()));
+            self.next(connection)
+        } else if flags & ClientConfigFlag::notification as u16 != 0 {
+            self.pending.push_back(None);
+            Some(send_characteristic_notification::new(
+                connection,
+                characteristic,
+                value.to_vec(),
+            ))
+        } else {
+            None
+        }
+    }
+
+    pub fn handle(&mut self, message: &Message) -> Option<Message> {
+        match message.payload {
+            MessagePayload::evt_gatt_server_characteristic_status(ref x) => {
+                if x.status_flags == CharacteristicStatusFlag::client_config as u8 {
+                    let key = (x.connection, x.characteristic);
+                    if x.client_config_flags == 0 {
+                        self.subscriptions.remove(&key);
+                    } else {
+                        self.subscriptions.insert(key, x.client_config_flags);
+                    }
+                    if x.client_config_flags & ClientConfigFlag::indication as u16 == 0 {
+                        if let Some(queue) = self.queues.get_mut(&x.connection) {
+                            queue.retain(|y| y.0 != x.characteristic);
+                        }
+                    }
+                    None
+                } else if x.status_flags == CharacteristicStatusFlag::confirmation as u8 {
+                    self.in_flight.remove(&x.connection);
+                    self.next(x.connection)
+                } else {
+                    None
+                }
+            }
+            MessagePayload::rsp_gatt_server_send_characteristic_notification(ref x) => {
+                match self.pending.pop_front() {
+                    // A rejected indication will never be confirmed.
+                    Some(Some(connection)) if x.result != Error::success => {
+                        self.in_flight.remove(&connection);
+                        self.next(connection)
+                    }
+                    _ => None,
+                }
+            }
+            MessagePayload::evt_le_connection_closed(ref x) => {
+                let connection = x.connection;
+                self.subscriptions.retain(|key, _| key.0 != connection);
+                self.queues.remove(&connection);
+                self.in_flight.remove(&connection);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn next(&mut self, connection: u8) -> Option<Message> {
+        if self.in_flight.contains(&connection) {
+            return None;
+        }
+        let (characteristic, value) = self.queues.get_mut(&connection)?.pop_front()?;
+        self.in_flight.insert(connection);
+        self.pending.push_back(Some(connection));
+        Some(send_characteristic_notification::new(
+            connection,
+            characteristic,
+            value,
+        ))
+    }
+}
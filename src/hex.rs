@@ -0,0 +1,17 @@
+use std::str;
+
+// Hexadecimal text for the files written by the crate, lowercase without
+// separators.
+pub fn to_hex(data: &[u8]) -> String {
+    data.iter().map(|x| format!("{:02x}", x)).collect()
+}
+
+pub fn from_hex(text: &str) -> Option<Vec<u8>> {
+    text.as_bytes()
+        .chunks(2)
+        .map(|x| match x.len() {
+            2 => u8::from_str_radix(str::from_utf8(x).ok()?, 16).ok(),
+            _ => None,
+        })
+        .collect()
+}
@@ -119,6 +119,43 @@ impl Into<Vec<u8>> for get_rssi {
     }
 }
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct read_channel_map {
+    pub connection: u8,
+}
+
+impl read_channel_map {
+    pub fn new(connection: u8) -> Message {
+        let header = MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x01,
+            message_class: MessageClass::le_connection,
+            message_id: 0x06,
+        };
+        let payload = read_channel_map { connection };
+        let payload = MessagePayload::cmd_le_connection_read_channel_map(payload);
+        Message { header, payload }
+    }
+}
+
+impl From<&[u8]> for read_channel_map {
+    fn from(data: &[u8]) -> read_channel_map {
+        let mut cursor = Cursor::new(data);
+        read_channel_map {
+            connection: cursor.get_u8(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for read_channel_map {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u8(self.connection);
+        bytes
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct set_parameters {
@@ -219,3 +256,121 @@ impl Into<Vec<u8>> for set_phy {
         bytes
     }
 }
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct set_preferred_phy {
+    pub connection: u8,
+    pub preferred_phy: u8,
+    pub accepted_phy: u8,
+}
+
+impl set_preferred_phy {
+    pub fn new(connection: u8, preferred_phy: u8, accepted_phy: u8) -> Message {
+        let header = MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x03,
+            message_class: MessageClass::le_connection,
+            message_id: 0x07,
+        };
+        let payload = set_preferred_phy {
+            connection,
+            preferred_phy,
+            accepted_phy,
+        };
+        let payload = MessagePayload::cmd_le_connection_set_preferred_phy(payload);
+        Message { header, payload }
+    }
+}
+
+impl From<&[u8]> for set_preferred_phy {
+    fn from(data: &[u8]) -> set_preferred_phy {
+        let mut cursor = Cursor::new(data);
+        set_preferred_phy {
+            connection: cursor.get_u8(),
+            preferred_phy: cursor.get_u8(),
+            accepted_phy: cursor.get_u8(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for set_preferred_phy {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u8(self.connection);
+        bytes.put_u8(self.preferred_phy);
+        bytes.put_u8(self.accepted_phy);
+        bytes
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct set_timing_parameters {
+    pub connection: u8,
+    pub min_interval: u16,
+    pub max_interval: u16,
+    pub latency: u16,
+    pub timeout: u16,
+    pub min_ce_length: u16,
+    pub max_ce_length: u16,
+}
+
+impl set_timing_parameters {
+    pub fn new(
+        connection: u8,
+        min_interval: u16,
+        max_interval: u16,
+        latency: u16,
+        timeout: u16,
+        min_ce_length: u16,
+        max_ce_length: u16,
+    ) -> Message {
+        let header = MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x0d,
+            message_class: MessageClass::le_connection,
+            message_id: 0x05,
+        };
+        let payload = set_timing_parameters {
+            connection,
+            min_interval,
+            max_interval,
+            latency,
+            timeout,
+            min_ce_length,
+            max_ce_length,
+        };
+        let payload = MessagePayload::cmd_le_connection_set_timing_parameters(payload);
+        Message { header, payload }
+    }
+}
+
+impl From<&[u8]> for set_timing_parameters {
+    fn from(data: &[u8]) -> set_timing_parameters {
+        let mut cursor = Cursor::new(data);
+        set_timing_parameters {
+            connection: cursor.get_u8(),
+            min_interval: cursor.get_u16_le(),
+            max_interval: cursor.get_u16_le(),
+            latency: cursor.get_u16_le(),
+            timeout: cursor.get_u16_le(),
+            min_ce_length: cursor.get_u16_le(),
+            max_ce_length: cursor.get_u16_le(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for set_timing_parameters {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u8(self.connection);
+        bytes.put_u16_le(self.min_interval);
+        bytes.put_u16_le(self.max_interval);
+        bytes.put_u16_le(self.latency);
+        bytes.put_u16_le(self.timeout);
+        bytes.put_u16_le(self.min_ce_length);
+        bytes.put_u16_le(self.max_ce_length);
+        bytes
+    }
+}
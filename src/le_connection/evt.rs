@@ -1,6 +1,7 @@
 use bytes::{Buf, BufMut};
 use error::Error;
-use le_connection::Security;
+use le_connection::{Role, Security};
+use le_gap::AddressType;
 use num_traits::FromPrimitive;
 use std::io::{Cursor, Read};
 
@@ -34,8 +35,8 @@ impl Into<Vec<u8>> for closed {
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct opened {
     pub address: [u8; 6],
-    pub address_type: u8,
-    pub master: u8,
+    pub address_type: AddressType,
+    pub master: Role,
     pub connection: u8,
     pub bonding: u8,
     pub advertiser: u8,
@@ -51,8 +52,8 @@ impl From<&[u8]> for opened {
         address.reverse();
         opened {
             address,
-            address_type: cursor.get_u8(),
-            master: cursor.get_u8(),
+            address_type: FromPrimitive::from_u8(cursor.get_u8()).unwrap(),
+            master: FromPrimitive::from_u8(cursor.get_u8()).unwrap(),
             connection: cursor.get_u8(),
             bonding: cursor.get_u8(),
             advertiser: cursor.get_u8(),
@@ -64,8 +65,8 @@ impl Into<Vec<u8>> for opened {
     fn into(self) -> Vec<u8> {
         let mut bytes = Vec::new();
         bytes.extend(self.address.iter().rev());
-        bytes.put_u8(self.address_type);
-        bytes.put_u8(self.master);
+        bytes.put_u8(self.address_type.clone() as u8);
+        bytes.put_u8(self.master.clone() as u8);
         bytes.put_u8(self.connection);
         bytes.put_u8(self.bonding);
         bytes.put_u8(self.advertiser);
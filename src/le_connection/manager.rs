@@ -0,0 +1,171 @@
+use error::Error;
+use le_connection::cmd::{close, get_rssi, set_parameters, set_phy};
+use le_connection::{Role, Security};
+use le_gap::AddressType;
+use message::{Message, MessagePayload};
+use std::collections::BTreeMap;
+
+const NO_BONDING: u8 = 0xff;
+const NO_ADVERTISER: u8 = 0xff;
+const DEFAULT_MTU: u16 = 23;
+const PHY_1M: u8 = 0x01;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Connection {
+    pub connection: u8,
+    pub address: [u8; 6],
+    pub address_type: AddressType,
+    pub role: Role,
+    pub bonding: Option<u8>,
+    pub advertiser: Option<u8>, // Advertising set the connection was made on
+    pub interval: u16,          // Units of 1.25 ms
+    pub latency: u16,
+    pub timeout: u16, // Units of 10 ms
+    pub security_mode: Security,
+    pub txsize: u16,
+    pub phy: u8,
+    pub mtu: u16,
+    pub rssi: Option<i8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionEvent {
+    Opened(Connection),
+    Updated(Connection),
+    Closed(Connection, Error),
+}
+
+// Folds connection events into one record per connection handle.
+#[derive(Debug, Default)]
+pub struct ConnectionManager {
+    connections: BTreeMap<u8, Connection>,
+}
+
+impl ConnectionManager {
+    pub fn new() -> ConnectionManager {
+        ConnectionManager::default()
+    }
+
+    pub fn get(&self, connection: u8) -> Option<&Connection> {
+        self.connections.get(&connection)
+    }
+
+    pub fn find(&self, address: &[u8; 6]) -> Option<&Connection> {
+        self.connections.values().find(|x| x.address == *address)
+    }
+
+    pub fn connections(&self) -> Vec<&Connection> {
+        self.connections.values().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.connections.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.connections.is_empty()
+    }
+
+    pub fn close(&self, connection: u8) -> Option<Message> {
+        self.connections.get(&connection)?;
+        Some(close::new(connection))
+    }
+
+    pub fn set_parameters(
+        &self,
+        connection: u8,
+        min_interval: u16,
+        max_interval: u16,
+        latency: u16,
+        timeout: u16,
+    ) -> Option<Message> {
+        self.connections.get(&connection)?;
+        Some(set_parameters::new(
+            connection,
+            min_interval,
+            max_interval,
+            latency,
+            timeout,
+        ))
+    }
+
+    pub fn set_phy(&self, connection: u8, phy: u8) -> Option<Message> {
+        self.connections.get(&connection)?;
+        Some(set_phy::new(connection, phy))
+    }
+
+    pub fn get_rssi(&self, connection: u8) -> Option<Message> {
+        self.connections.get(&connection)?;
+        Some(get_rssi::new(connection))
+    }
+
+    pub fn handle(&mut self, message: &Message) -> Option<ConnectionEvent> {
+        match message.payload {
+            MessagePayload::evt_le_connection_opened(ref x) => {
+                let connection = Connection {
+                    connection: x.connection,
+                    address: x.address,
+                    address_type: x.address_type.clone(),
+                    role: x.master.clone(),
+                    bonding: if x.bonding == NO_BONDING {
+                        None
+                    } else {
+                        Some(x.bonding)
+                    },
+                    advertiser: if x.advertiser == NO_ADVERTISER {
+                        None
+                    } else {
+                        Some(x.advertiser)
+                    },
+                    interval: 0,
+                    latency: 0,
+                    timeout: 0,
+                    security_mode: Security::mode1_level1,
+                    txsize: 0,
+                    phy: PHY_1M,
+                    mtu: DEFAULT_MTU,
+                    rssi: None,
+                };
+                self.connections.insert(x.connection, connection.clone());
+                Some(ConnectionEvent::Opened(connection))
+            }
+            MessagePayload::evt_le_connection_closed(ref x) => {
+                let connection = self.connections.remove(&x.connection)?;
+                Some(ConnectionEvent::Closed(connection, x.reason.clone()))
+            }
+            MessagePayload::evt_le_connection_parameters(ref x) => {
+                self.update(x.connection, |connection| {
+                    connection.interval = x.interval;
+                    connection.latency = x.latency;
+                    connection.timeout = x.timeout;
+                    connection.security_mode = x.security_mode.clone();
+                    connection.txsize = x.txsize;
+                })
+            }
+            MessagePayload::evt_le_connection_phy_status(ref x) => {
+                self.update(x.connection, |connection| connection.phy = x.phy)
+            }
+            MessagePayload::evt_le_connection_rssi(ref x) if x.status == 0 => {
+                self.update(x.connection, |connection| connection.rssi = Some(x.rssi))
+            }
+            MessagePayload::evt_gatt_mtu_exchanged(ref x) => {
+                self.update(x.connection, |connection| connection.mtu = x.mtu)
+            }
+            MessagePayload::evt_sm_bonded(ref x) if x.bonding != NO_BONDING => self
+                .update(x.connection, |connection| {
+                    connection.bonding = Some(x.bonding)
+                }),
+            _ => None,
+        }
+    }
+
+    fn update<F: FnOnce(&mut Connection)>(
+        &mut self,
+        connection: u8,
+        f: F,
+    ) -> Option<ConnectionEvent> {
+        let connection = self.connections.get_mut(&connection)?;
+        f(connection);
+        Some(ConnectionEvent::Updated(connection.clone()))
+    }
+}
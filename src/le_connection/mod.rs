@@ -1,6 +1,8 @@
 pub mod cmd;
 pub mod evt;
+pub mod manager;
 pub mod rsp;
+pub mod rssi;
 
 use message::{MessageClass, MessageHeader, MessagePayload, MessageType};
 use num_derive::FromPrimitive;
@@ -35,6 +37,15 @@ pub fn parse(header: &MessageHeader, buffer: &[u8]) -> Result<MessagePayload, Er
             rsp::get_rssi::from(buffer),
         )),
 
+        MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: _,
+            message_class: MessageClass::le_connection,
+            message_id: 0x06,
+        } => Ok(MessagePayload::rsp_le_connection_read_channel_map(
+            rsp::read_channel_map::from(buffer),
+        )),
+
         MessageHeader {
             message_type: MessageType::command_response,
             payload_length: 0x02,
@@ -53,6 +64,24 @@ pub fn parse(header: &MessageHeader, buffer: &[u8]) -> Result<MessagePayload, Er
             rsp::set_phy::from(buffer),
         )),
 
+        MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x02,
+            message_class: MessageClass::le_connection,
+            message_id: 0x07,
+        } => Ok(MessagePayload::rsp_le_connection_set_preferred_phy(
+            rsp::set_preferred_phy::from(buffer),
+        )),
+
+        MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x02,
+            message_class: MessageClass::le_connection,
+            message_id: 0x05,
+        } => Ok(MessagePayload::rsp_le_connection_set_timing_parameters(
+            rsp::set_timing_parameters::from(buffer),
+        )),
+
         MessageHeader {
             message_type: MessageType::event,
             payload_length: 0x03,
@@ -102,6 +131,13 @@ pub fn parse(header: &MessageHeader, buffer: &[u8]) -> Result<MessagePayload, Er
     }
 }
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, PartialEq, PartialOrd, Clone, FromPrimitive)]
+pub enum Role {
+    slave = 0,  // Peripheral
+    master = 1, // Central
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, PartialEq, PartialOrd, Clone, FromPrimitive)]
 pub enum Security {
@@ -110,3 +146,11 @@ pub enum Security {
     mode1_level3 = 2, // Authenticated pairing with encryption
     mode1_level4 = 3, // Authenticated Secure Connections pairing with encryption using a 128-bit strength encryption key
 }
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ConnectionParameters {
+    pub min_interval: u16, // Units of 1.25 ms
+    pub max_interval: u16, // Units of 1.25 ms
+    pub latency: u16,
+    pub timeout: u16, // Units of 10 ms
+}
@@ -1,7 +1,7 @@
 use bytes::{Buf, BufMut};
 use error::Error;
 use num_traits::FromPrimitive;
-use std::io::Cursor;
+use std::io::{Cursor, Read};
 
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
@@ -72,6 +72,39 @@ impl Into<Vec<u8>> for get_rssi {
     }
 }
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct read_channel_map {
+    pub result: Error,
+    pub channel_map: Vec<u8>,
+}
+
+impl From<&[u8]> for read_channel_map {
+    fn from(data: &[u8]) -> read_channel_map {
+        let mut cursor = Cursor::new(data);
+        let result = FromPrimitive::from_u16(cursor.get_u16_le()).unwrap();
+        let mut channel_map = Vec::new();
+        cursor.get_u8();
+        cursor
+            .read_to_end(&mut channel_map)
+            .expect("Failed to read bytes.");
+        read_channel_map {
+            result,
+            channel_map,
+        }
+    }
+}
+
+impl Into<Vec<u8>> for read_channel_map {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u16_le(self.result.clone() as u16);
+        bytes.put_u8(self.channel_map.len() as u8);
+        bytes.extend(self.channel_map.iter());
+        bytes
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct set_parameters {
@@ -117,3 +150,49 @@ impl Into<Vec<u8>> for set_phy {
         bytes
     }
 }
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct set_preferred_phy {
+    pub result: Error,
+}
+
+impl From<&[u8]> for set_preferred_phy {
+    fn from(data: &[u8]) -> set_preferred_phy {
+        let mut cursor = Cursor::new(data);
+        set_preferred_phy {
+            result: FromPrimitive::from_u16(cursor.get_u16_le()).unwrap(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for set_preferred_phy {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u16_le(self.result.clone() as u16);
+        bytes
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct set_timing_parameters {
+    pub result: Error,
+}
+
+impl From<&[u8]> for set_timing_parameters {
+    fn from(data: &[u8]) -> set_timing_parameters {
+        let mut cursor = Cursor::new(data);
+        set_timing_parameters {
+            result: FromPrimitive::from_u16(cursor.get_u16_le()).unwrap(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for set_timing_parameters {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u16_le(self.result.clone() as u16);
+        bytes
+    }
+}
@@ -0,0 +1,263 @@
+use le_connection::cmd::get_rssi;
+use le_gap::adv_data;
+use message::{Message, MessagePayload};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+const TX_POWER_NOT_AVAILABLE: i8 = 127;
+const PATH_LOSS_AT_1M: f64 = 41.0; // dB, typical for 2.4 GHz
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RssiConfig {
+    pub poll_interval: Duration,
+    pub window: usize,
+    pub process_noise: f64, // Kalman filter, variance of the true RSSI between samples
+    pub measurement_noise: f64, // Kalman filter, variance of a single sample
+    pub near: i8,           // Filtered RSSI at or above which a device is near
+    pub far: i8,            // Filtered RSSI at or below which a device is far
+    pub path_loss_exponent: f64, // 2.0 in free space, 2.5 to 4.0 indoors
+}
+
+impl Default for RssiConfig {
+    fn default() -> RssiConfig {
+        RssiConfig {
+            poll_interval: Duration::from_secs(1),
+            window: 16,
+            process_noise: 0.05,
+            measurement_noise: 4.0,
+            near: -60,
+            far: -75,
+            path_loss_exponent: 2.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Proximity {
+    Near,
+    Far,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RssiEvent {
+    Proximity([u8; 6], Proximity),
+}
+
+// Samples of one device over the configured window, with a Kalman estimate
+// over the same samples.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RssiStats {
+    samples: VecDeque<i8>,
+    estimate: f64,
+    variance: f64,
+    pub tx_power: Option<i8>, // Advertised, in dBm
+    pub proximity: Option<Proximity>,
+}
+
+impl RssiStats {
+    fn new() -> RssiStats {
+        RssiStats {
+            samples: VecDeque::new(),
+            estimate: f64::NAN,
+            variance: 0.0,
+            tx_power: None,
+            proximity: None,
+        }
+    }
+
+    fn add(&mut self, rssi: i8, config: &RssiConfig) {
+        if self.samples.len() == config.window.max(1) {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(rssi);
+        // Filtered again from the oldest sample so that samples leaving the
+        // window no longer count.
+        self.estimate = f64::NAN;
+        for rssi in self.samples.iter().map(|x| f64::from(*x)) {
+            if self.estimate.is_nan() {
+                self.estimate = rssi;
+                self.variance = config.measurement_noise;
+                continue;
+            }
+            self.variance += config.process_noise;
+            let gain = self.variance / (self.variance + config.measurement_noise);
+            self.estimate += gain * (rssi - self.estimate);
+            self.variance *= 1.0 - gain;
+        }
+    }
+
+    // Forgets the samples but keeps the TX power.
+    fn reset(&mut self) {
+        *self = RssiStats {
+            tx_power: self.tx_power,
+            ..RssiStats::new()
+        };
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn last(&self) -> Option<i8> {
+        self.samples.back().cloned()
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let sum: f64 = self.samples.iter().map(|x| f64::from(*x)).sum();
+        Some(sum / self.samples.len() as f64)
+    }
+
+    pub fn median(&self) -> Option<f64> {
+        let mut sorted: Vec<i8> = self.samples.iter().cloned().collect();
+        sorted.sort();
+        let middle = sorted.len() / 2;
+        match sorted.len() {
+            0 => None,
+            x if x % 2 == 1 => Some(f64::from(sorted[middle])),
+            _ => Some((f64::from(sorted[middle - 1]) + f64::from(sorted[middle])) / 2.0),
+        }
+    }
+
+    pub fn kalman(&self) -> Option<f64> {
+        if self.estimate.is_nan() {
+            None
+        } else {
+            Some(self.estimate)
+        }
+    }
+
+    // Log-distance path loss estimate in meters from the filtered RSSI,
+    // available once the device has advertised its TX power.
+    pub fn distance(&self, path_loss_exponent: f64) -> Option<f64> {
+        let rssi = self.kalman()?;
+        let reference = f64::from(self.tx_power?) - PATH_LOSS_AT_1M;
+        Some(10f64.powf((reference - rssi) / (10.0 * path_loss_exponent)))
+    }
+}
+
+// Keeps RSSI statistics per device address. Connected peers are polled with
+// le_connection_get_rssi, other devices are sampled from scan reports.
+#[derive(Debug)]
+pub struct RssiMonitor {
+    config: RssiConfig,
+    devices: HashMap<[u8; 6], RssiStats>,
+    connections: BTreeMap<u8, ([u8; 6], Option<Instant>)>, // Address and next poll
+}
+
+impl RssiMonitor {
+    pub fn new(config: RssiConfig) -> RssiMonitor {
+        RssiMonitor {
+            config,
+            devices: HashMap::new(),
+            connections: BTreeMap::new(),
+        }
+    }
+
+    pub fn stats(&self, address: &[u8; 6]) -> Option<&RssiStats> {
+        self.devices.get(address)
+    }
+
+    pub fn distance(&self, address: &[u8; 6]) -> Option<f64> {
+        self.devices
+            .get(address)?
+            .distance(self.config.path_loss_exponent)
+    }
+
+    pub fn forget(&mut self, address: &[u8; 6]) {
+        self.devices.remove(address);
+    }
+
+    // Commands for the connections due to be polled.
+    pub fn poll(&mut self, now: Instant) -> Vec<Message> {
+        let interval = self.config.poll_interval;
+        let mut commands = Vec::new();
+        for (connection, entry) in self.connections.iter_mut() {
+            match entry.1 {
+                Some(next) if next > now => (),
+                _ => {
+                    entry.1 = Some(now + interval);
+                    commands.push(get_rssi::new(*connection));
+                }
+            }
+        }
+        commands
+    }
+
+    pub fn handle(&mut self, message: &Message) -> Option<RssiEvent> {
+        match message.payload {
+            MessagePayload::evt_le_connection_opened(ref x) => {
+                self.connections.insert(x.connection, (x.address, None));
+                None
+            }
+            // The polled samples and proximity are stale once the peer is
+            // only seen in scan reports, which would otherwise never report
+            // it far again.
+            MessagePayload::evt_le_connection_closed(ref x) => {
+                let (address, _) = self.connections.remove(&x.connection)?;
+                if let Some(stats) = self.devices.get_mut(&address) {
+                    stats.reset();
+                }
+                None
+            }
+            MessagePayload::evt_le_connection_rssi(ref x) if x.status == 0 => {
+                let address = self.connections.get(&x.connection)?.0;
+                self.sample(address, x.rssi, None)
+            }
+            MessagePayload::evt_le_gap_scan_response(ref x) => {
+                let tx_power = adv_data::tx_power_level(&x.data);
+                self.scanned(x.address, x.rssi, tx_power)
+            }
+            MessagePayload::evt_le_gap_extended_scan_response(ref x) => {
+                let tx_power = if x.tx_power == TX_POWER_NOT_AVAILABLE {
+                    adv_data::tx_power_level(&x.data)
+                } else {
+                    Some(x.tx_power)
+                };
+                self.scanned(x.address, x.rssi, tx_power)
+            }
+            _ => None,
+        }
+    }
+
+    // Scan reports are ignored for connected peers, which are polled.
+    fn scanned(&mut self, address: [u8; 6], rssi: i8, tx_power: Option<i8>) -> Option<RssiEvent> {
+        if self.connections.values().any(|x| x.0 == address) {
+            if let (Some(stats), Some(_)) = (self.devices.get_mut(&address), tx_power) {
+                stats.tx_power = tx_power;
+            }
+            return None;
+        }
+        self.sample(address, rssi, tx_power)
+    }
+
+    fn sample(&mut self, address: [u8; 6], rssi: i8, tx_power: Option<i8>) -> Option<RssiEvent> {
+        let config = &self.config;
+        let stats = self.devices.entry(address).or_insert_with(RssiStats::new);
+        if tx_power.is_some() {
+            stats.tx_power = tx_power;
+        }
+        stats.add(rssi, config);
+        let estimate = stats.kalman()?;
+        // Hysteresis between the two thresholds keeps a device hovering
+        // around one of them from flapping.
+        let proximity = if estimate >= f64::from(config.near) {
+            Proximity::Near
+        } else if estimate <= f64::from(config.far) {
+            Proximity::Far
+        } else {
+            return None;
+        };
+        if stats.proximity == Some(proximity) {
+            return None;
+        }
+        stats.proximity = Some(proximity);
+        Some(RssiEvent::Proximity(address, proximity))
+    }
+}
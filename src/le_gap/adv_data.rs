@@ -0,0 +1,118 @@
+use bytes::{Buf, BufMut};
+use std::io::Cursor;
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum AdType {
+    flags = 0x01,                        // Flags
+    incomplete_list_16bit_uuids = 0x02,  // Incomplete List of 16-bit Service Class UUIDs
+    complete_list_16bit_uuids = 0x03,    // Complete List of 16-bit Service Class UUIDs
+    incomplete_list_32bit_uuids = 0x04,  // Incomplete List of 32-bit Service Class UUIDs
+    complete_list_32bit_uuids = 0x05,    // Complete List of 32-bit Service Class UUIDs
+    incomplete_list_128bit_uuids = 0x06, // Incomplete List of 128-bit Service Class UUIDs
+    complete_list_128bit_uuids = 0x07,   // Complete List of 128-bit Service Class UUIDs
+    shortened_local_name = 0x08,         // Shortened Local Name
+    complete_local_name = 0x09,          // Complete Local Name
+    tx_power_level = 0x0a,               // Tx Power Level
+    service_data_16bit_uuid = 0x16,      // Service Data - 16-bit UUID
+    appearance = 0x19,                   // Appearance
+    manufacturer_specific_data = 0xff,   // Manufacturer Specific Data
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct AdStructure {
+    pub ad_type: u8,
+    pub data: Vec<u8>,
+}
+
+impl AdStructure {
+    pub fn new(ad_type: AdType, data: Vec<u8>) -> AdStructure {
+        AdStructure {
+            ad_type: ad_type as u8,
+            data,
+        }
+    }
+}
+
+// Splits advertising or scan response data into its AD structures. Malformed
+// trailing bytes are ignored.
+pub fn parse(data: &[u8]) -> Vec<AdStructure> {
+    let mut structures = Vec::new();
+    let mut cursor = Cursor::new(data);
+    while cursor.remaining() > 0 {
+        let length = cursor.get_u8() as usize;
+        if length == 0 || length > cursor.remaining() {
+            break;
+        }
+        let ad_type = cursor.get_u8();
+        let start = cursor.position() as usize;
+        let data = data[start..start + length - 1].to_vec();
+        cursor.set_position((start + length - 1) as u64);
+        structures.push(AdStructure { ad_type, data });
+    }
+    structures
+}
+
+pub fn encode(structures: &[AdStructure]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for structure in structures {
+        bytes.put_u8(structure.data.len() as u8 + 1);
+        bytes.put_u8(structure.ad_type);
+        bytes.extend(structure.data.iter());
+    }
+    bytes
+}
+
+// Complete local name if present, shortened local name otherwise.
+pub fn local_name(data: &[u8]) -> Option<String> {
+    let structures = parse(data);
+    let complete = structures
+        .iter()
+        .find(|x| x.ad_type == AdType::complete_local_name as u8);
+    let shortened = structures
+        .iter()
+        .find(|x| x.ad_type == AdType::shortened_local_name as u8);
+    complete
+        .or(shortened)
+        .map(|x| String::from_utf8_lossy(&x.data).into_owned())
+}
+
+// Service UUIDs listed in the data, most significant byte first as in
+// `gatt::evt::service.uuid`.
+pub fn service_uuids(data: &[u8]) -> Vec<Vec<u8>> {
+    let mut uuids = Vec::new();
+    for structure in parse(data) {
+        let size = match structure.ad_type {
+            0x02 | 0x03 => 2,
+            0x04 | 0x05 => 4,
+            0x06 | 0x07 => 16,
+            _ => continue,
+        };
+        for chunk in structure.data.chunks(size) {
+            if chunk.len() == size {
+                uuids.push(chunk.iter().rev().cloned().collect());
+            }
+        }
+    }
+    uuids
+}
+
+// Manufacturer specific data as (company identifier, data) pairs.
+pub fn manufacturer_data(data: &[u8]) -> Vec<(u16, Vec<u8>)> {
+    parse(data)
+        .into_iter()
+        .filter(|x| x.ad_type == AdType::manufacturer_specific_data as u8 && x.data.len() >= 2)
+        .map(|x| {
+            let mut cursor = Cursor::new(&x.data);
+            let company = cursor.get_u16_le();
+            (company, x.data[2..].to_vec())
+        })
+        .collect()
+}
+
+pub fn tx_power_level(data: &[u8]) -> Option<i8> {
+    parse(data)
+        .into_iter()
+        .find(|x| x.ad_type == AdType::tx_power_level as u8 && x.data.len() == 1)
+        .map(|x| x.data[0] as i8)
+}
@@ -0,0 +1,335 @@
+use error::Error;
+use le_gap::cmd::{
+    bt5_set_adv_data, clear_advertise_configuration, set_advertise_channel_map,
+    set_advertise_configuration, set_advertise_phy, set_advertise_report_scan_request,
+    set_advertise_timing, set_advertise_tx_power, start_advertising, start_periodic_advertising,
+    stop_advertising, stop_periodic_advertising,
+};
+use le_gap::{ConnectableMode, DiscoverableMode, PhyType};
+use message::{Message, MessagePayload};
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+
+const ADV_DATA: u8 = 0;
+const SCAN_RSP_DATA: u8 = 1;
+const PERIODIC_ADV_DATA: u8 = 8;
+const ALL_CONFIGURATIONS: u32 = 0xffff_ffff;
+const NO_ADVERTISER: u8 = 0xff;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdvertisingConfig {
+    pub interval_min: Duration,
+    pub interval_max: Duration,
+    pub duration: Option<Duration>, // None to advertise until stopped
+    pub max_events: u8,             // 0 for no limit
+    pub primary_phy: PhyType,
+    pub secondary_phy: PhyType,
+    pub channel_map: u8, // Bit 0 for channel 37, bit 1 for 38 and bit 2 for 39
+    pub tx_power: Option<i16>, // Units of 0.1 dBm
+    pub configurations: u32, // Flags for le_gap_set_advertise_configuration
+    pub report_scan_requests: bool,
+    pub adv_data: Option<Vec<u8>>,
+    pub scan_rsp_data: Option<Vec<u8>>,
+    pub discover: DiscoverableMode,
+    pub connect: ConnectableMode,
+}
+
+impl Default for AdvertisingConfig {
+    fn default() -> AdvertisingConfig {
+        AdvertisingConfig {
+            interval_min: Duration::from_millis(100),
+            interval_max: Duration::from_millis(200),
+            duration: None,
+            max_events: 0,
+            primary_phy: PhyType::phy_1m,
+            secondary_phy: PhyType::phy_1m,
+            channel_map: 0x07,
+            tx_power: None,
+            configurations: 0,
+            report_scan_requests: false,
+            adv_data: None,
+            scan_rsp_data: None,
+            discover: DiscoverableMode::general_discoverable,
+            connect: ConnectableMode::connectable_scannable,
+        }
+    }
+}
+
+impl AdvertisingConfig {
+    // Commands that apply the configuration to an advertising set, in the
+    // order the stack expects them before le_gap_start_advertising.
+    pub fn commands(&self, handle: u8) -> Vec<Message> {
+        let mut commands = vec![
+            set_advertise_timing::new(
+                handle,
+                to_units(self.interval_min, 625),
+                to_units(self.interval_max, 625),
+                self.duration.map_or(0, |x| to_short_units(x, 10_000)),
+                self.max_events,
+            ),
+            set_advertise_channel_map::new(handle, self.channel_map),
+            set_advertise_phy::new(handle, self.primary_phy.clone(), self.secondary_phy.clone()),
+        ];
+        if let Some(power) = self.tx_power {
+            commands.push(set_advertise_tx_power::new(handle, power));
+        }
+        commands.push(clear_advertise_configuration::new(
+            handle,
+            ALL_CONFIGURATIONS,
+        ));
+        if self.configurations != 0 {
+            commands.push(set_advertise_configuration::new(
+                handle,
+                self.configurations,
+            ));
+        }
+        commands.push(set_advertise_report_scan_request::new(
+            handle,
+            self.report_scan_requests as u8,
+        ));
+        if let Some(ref data) = self.adv_data {
+            commands.push(bt5_set_adv_data::new(handle, ADV_DATA, data.clone()));
+        }
+        if let Some(ref data) = self.scan_rsp_data {
+            commands.push(bt5_set_adv_data::new(handle, SCAN_RSP_DATA, data.clone()));
+        }
+        commands
+    }
+}
+
+// Converts a duration to the stack's time units, given in microseconds.
+// Durations too long for the field are clamped to its maximum.
+fn to_units(duration: Duration, unit_us: u64) -> u32 {
+    let us = duration
+        .as_secs()
+        .saturating_mul(1_000_000)
+        .saturating_add(u64::from(duration.subsec_micros()));
+    (us / unit_us).min(u64::from(u32::MAX)) as u32
+}
+
+fn to_short_units(duration: Duration, unit_us: u64) -> u16 {
+    to_units(duration, unit_us).min(u32::from(u16::MAX)) as u16
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AdvertisingState {
+    Idle,
+    Starting,
+    Advertising,
+    Stopping,
+    Stopped,
+    TimedOut,
+    Connected(u8),
+    Failed(Error),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdvertisingEvent {
+    pub handle: u8,
+    pub state: AdvertisingState,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdvertisingSet {
+    pub handle: u8,
+    pub config: AdvertisingConfig,
+    pub state: AdvertisingState,
+    pub periodic: bool,
+}
+
+#[derive(Debug)]
+pub struct Advertiser {
+    max_sets: u8,
+    sets: BTreeMap<u8, AdvertisingSet>,
+    pending_configs: VecDeque<u8>, // One entry per configuration command sent
+    pending_starts: VecDeque<u8>,
+    pending_stops: VecDeque<u8>,
+}
+
+impl Advertiser {
+    pub fn new(max_sets: u8) -> Advertiser {
+        Advertiser {
+            max_sets,
+            sets: BTreeMap::new(),
+            pending_configs: VecDeque::new(),
+            pending_starts: VecDeque::new(),
+            pending_stops: VecDeque::new(),
+        }
+    }
+
+    // Allocates the lowest free advertising set handle.
+    pub fn create(&mut self, config: AdvertisingConfig) -> Option<u8> {
+        let handle = (0..self.max_sets).find(|x| !self.sets.contains_key(x))?;
+        self.sets.insert(
+            handle,
+            AdvertisingSet {
+                handle,
+                config,
+                state: AdvertisingState::Idle,
+                periodic: false,
+            },
+        );
+        Some(handle)
+    }
+
+    pub fn remove(&mut self, handle: u8) -> Vec<Message> {
+        let commands = match self.sets.get(&handle) {
+            Some(set) if is_active(&set.state) => self.stop(handle),
+            _ => Vec::new(),
+        };
+        self.sets.remove(&handle);
+        commands
+    }
+
+    pub fn set(&self, handle: u8) -> Option<&AdvertisingSet> {
+        self.sets.get(&handle)
+    }
+
+    pub fn sets(&self) -> Vec<&AdvertisingSet> {
+        self.sets.values().collect()
+    }
+
+    // Replaces the configuration of a set. An active set is restarted so the
+    // new configuration takes effect.
+    pub fn configure(&mut self, handle: u8, config: AdvertisingConfig) -> Vec<Message> {
+        let restart = match self.sets.get_mut(&handle) {
+            Some(set) => {
+                set.config = config;
+                is_active(&set.state)
+            }
+            None => return Vec::new(),
+        };
+        if restart {
+            let mut commands = self.stop(handle);
+            commands.append(&mut self.start(handle));
+            commands
+        } else {
+            Vec::new()
+        }
+    }
+
+    pub fn start(&mut self, handle: u8) -> Vec<Message> {
+        let set = match self.sets.get_mut(&handle) {
+            Some(set) => set,
+            None => return Vec::new(),
+        };
+        let mut commands = set.config.commands(handle);
+        for _ in &commands {
+            self.pending_configs.push_back(handle);
+        }
+        commands.push(start_advertising::new(
+            handle,
+            set.config.discover.clone(),
+            set.config.connect.clone(),
+        ));
+        set.state = AdvertisingState::Starting;
+        self.pending_starts.push_back(handle);
+        commands
+    }
+
+    pub fn stop(&mut self, handle: u8) -> Vec<Message> {
+        let set = match self.sets.get_mut(&handle) {
+            Some(set) => set,
+            None => return Vec::new(),
+        };
+        set.state = AdvertisingState::Stopping;
+        self.pending_stops.push_back(handle);
+        vec![stop_advertising::new(handle)]
+    }
+
+    // Periodic advertising runs on top of the set's extended advertising, so
+    // the set must be started with a non-connectable, non-scannable mode.
+    pub fn start_periodic(
+        &mut self,
+        handle: u8,
+        interval_min: Duration,
+        interval_max: Duration,
+        data: Vec<u8>,
+    ) -> Vec<Message> {
+        match self.sets.get_mut(&handle) {
+            Some(set) => set.periodic = true,
+            None => return Vec::new(),
+        }
+        self.pending_configs.push_back(handle);
+        vec![
+            bt5_set_adv_data::new(handle, PERIODIC_ADV_DATA, data),
+            start_periodic_advertising::new(
+                handle,
+                to_short_units(interval_min, 1250),
+                to_short_units(interval_max, 1250),
+                0,
+            ),
+        ]
+    }
+
+    pub fn stop_periodic(&mut self, handle: u8) -> Vec<Message> {
+        match self.sets.get_mut(&handle) {
+            Some(set) if set.periodic => set.periodic = false,
+            _ => return Vec::new(),
+        }
+        vec![stop_periodic_advertising::new(handle)]
+    }
+
+    pub fn handle(&mut self, message: &Message) -> Option<AdvertisingEvent> {
+        let (handle, state) = match message.payload {
+            MessagePayload::rsp_le_gap_start_advertising(ref x) => {
+                let handle = self.pending_starts.pop_front()?;
+                // A set whose configuration was rejected stays failed.
+                if let AdvertisingState::Failed(_) = self.sets.get(&handle)?.state {
+                    return None;
+                }
+                if x.result == Error::success {
+                    (handle, AdvertisingState::Advertising)
+                } else {
+                    (handle, AdvertisingState::Failed(x.result.clone()))
+                }
+            }
+            MessagePayload::rsp_le_gap_stop_advertising(ref x) => {
+                let handle = self.pending_stops.pop_front()?;
+                if x.result == Error::success {
+                    (handle, AdvertisingState::Stopped)
+                } else {
+                    (handle, AdvertisingState::Failed(x.result.clone()))
+                }
+            }
+            MessagePayload::evt_le_gap_adv_timeout(ref x) => (x.handle, AdvertisingState::TimedOut),
+            MessagePayload::evt_le_connection_opened(ref x) if x.advertiser != NO_ADVERTISER => {
+                (x.advertiser, AdvertisingState::Connected(x.connection))
+            }
+            ref payload => {
+                let result = configuration_result(payload)?;
+                let handle = self.pending_configs.pop_front()?;
+                if *result == Error::success {
+                    return None;
+                }
+                (handle, AdvertisingState::Failed(result.clone()))
+            }
+        };
+        let set = self.sets.get_mut(&handle)?;
+        set.state = state.clone();
+        Some(AdvertisingEvent { handle, state })
+    }
+}
+
+// Result of the commands sent by AdvertisingConfig::commands and
+// start_periodic, which carry no handle and are matched in order.
+fn configuration_result(payload: &MessagePayload) -> Option<&Error> {
+    match *payload {
+        MessagePayload::rsp_le_gap_set_advertise_timing(ref x) => Some(&x.result),
+        MessagePayload::rsp_le_gap_set_advertise_channel_map(ref x) => Some(&x.result),
+        MessagePayload::rsp_le_gap_set_advertise_phy(ref x) => Some(&x.result),
+        MessagePayload::rsp_le_gap_set_advertise_tx_power(ref x) => Some(&x.result),
+        MessagePayload::rsp_le_gap_clear_advertise_configuration(ref x) => Some(&x.result),
+        MessagePayload::rsp_le_gap_set_advertise_configuration(ref x) => Some(&x.result),
+        MessagePayload::rsp_le_gap_set_advertise_report_scan_request(ref x) => Some(&x.result),
+        MessagePayload::rsp_le_gap_bt5_set_adv_data(ref x) => Some(&x.result),
+        _ => None,
+    }
+}
+
+fn is_active(state: &AdvertisingState) -> bool {
+    matches!(
+        state,
+        AdvertisingState::Starting | AdvertisingState::Advertising
+    )
+}
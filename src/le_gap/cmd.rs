@@ -16,7 +16,7 @@ impl bt5_set_adv_data {
     pub fn new(handle: u8, scan_rsp: u8, adv_data: Vec<u8>) -> Message {
         let header = MessageHeader {
             message_type: MessageType::command_response,
-            payload_length: 0x03 + (1 + adv_data.len() as u8),
+            payload_length: 0x02 + (1 + adv_data.len() as u8),
             message_class: MessageClass::le_gap,
             message_id: 0x0c,
         };
@@ -102,6 +102,43 @@ impl Into<Vec<u8>> for clear_advertise_configuration {
     }
 }
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct clear_advertise_random_address {
+    pub handle: u8,
+}
+
+impl clear_advertise_random_address {
+    pub fn new(handle: u8) -> Message {
+        let header = MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x01,
+            message_class: MessageClass::le_gap,
+            message_id: 0x26,
+        };
+        let payload = clear_advertise_random_address { handle };
+        let payload = MessagePayload::cmd_le_gap_clear_advertise_random_address(payload);
+        Message { header, payload }
+    }
+}
+
+impl From<&[u8]> for clear_advertise_random_address {
+    fn from(data: &[u8]) -> clear_advertise_random_address {
+        let mut cursor = Cursor::new(data);
+        clear_advertise_random_address {
+            handle: cursor.get_u8(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for clear_advertise_random_address {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u8(self.handle);
+        bytes
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct connect {
@@ -154,6 +191,43 @@ impl Into<Vec<u8>> for connect {
     }
 }
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct enable_whitelisting {
+    pub enable: u8,
+}
+
+impl enable_whitelisting {
+    pub fn new(enable: u8) -> Message {
+        let header = MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x01,
+            message_class: MessageClass::le_gap,
+            message_id: 0x21,
+        };
+        let payload = enable_whitelisting { enable };
+        let payload = MessagePayload::cmd_le_gap_enable_whitelisting(payload);
+        Message { header, payload }
+    }
+}
+
+impl From<&[u8]> for enable_whitelisting {
+    fn from(data: &[u8]) -> enable_whitelisting {
+        let mut cursor = Cursor::new(data);
+        enable_whitelisting {
+            enable: cursor.get_u8(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for enable_whitelisting {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u8(self.enable);
+        bytes
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct end_procedure {}
@@ -317,6 +391,60 @@ impl Into<Vec<u8>> for set_advertise_phy {
     }
 }
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct set_advertise_random_address {
+    pub handle: u8,
+    pub addr_type: u8,
+    pub address: [u8; 6],
+}
+
+impl set_advertise_random_address {
+    pub fn new(handle: u8, addr_type: u8, address: [u8; 6]) -> Message {
+        let header = MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x08,
+            message_class: MessageClass::le_gap,
+            message_id: 0x25,
+        };
+        let payload = set_advertise_random_address {
+            handle,
+            addr_type,
+            address,
+        };
+        let payload = MessagePayload::cmd_le_gap_set_advertise_random_address(payload);
+        Message { header, payload }
+    }
+}
+
+impl From<&[u8]> for set_advertise_random_address {
+    fn from(data: &[u8]) -> set_advertise_random_address {
+        let mut cursor = Cursor::new(data);
+        let handle = cursor.get_u8();
+        let addr_type = cursor.get_u8();
+        let mut address: [u8; 6] = Default::default();
+        cursor
+            .read_exact(&mut address)
+            .expect("Failed to read bytes.");
+        address.reverse();
+        set_advertise_random_address {
+            handle,
+            addr_type,
+            address,
+        }
+    }
+}
+
+impl Into<Vec<u8>> for set_advertise_random_address {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u8(self.handle);
+        bytes.put_u8(self.addr_type);
+        bytes.extend(self.address.iter().rev());
+        bytes
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct set_advertise_report_scan_request {
@@ -512,6 +640,115 @@ impl Into<Vec<u8>> for set_conn_parameters {
     }
 }
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct set_conn_phy {
+    pub preferred_phy: u8,
+    pub accepted_phy: u8,
+}
+
+impl set_conn_phy {
+    pub fn new(preferred_phy: u8, accepted_phy: u8) -> Message {
+        let header = MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x02,
+            message_class: MessageClass::le_gap,
+            message_id: 0x09,
+        };
+        let payload = set_conn_phy {
+            preferred_phy,
+            accepted_phy,
+        };
+        let payload = MessagePayload::cmd_le_gap_set_conn_phy(payload);
+        Message { header, payload }
+    }
+}
+
+impl From<&[u8]> for set_conn_phy {
+    fn from(data: &[u8]) -> set_conn_phy {
+        let mut cursor = Cursor::new(data);
+        set_conn_phy {
+            preferred_phy: cursor.get_u8(),
+            accepted_phy: cursor.get_u8(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for set_conn_phy {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u8(self.preferred_phy);
+        bytes.put_u8(self.accepted_phy);
+        bytes
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct set_conn_timing_parameters {
+    pub min_interval: u16,
+    pub max_interval: u16,
+    pub latency: u16,
+    pub timeout: u16,
+    pub min_ce_length: u16,
+    pub max_ce_length: u16,
+}
+
+impl set_conn_timing_parameters {
+    pub fn new(
+        min_interval: u16,
+        max_interval: u16,
+        latency: u16,
+        timeout: u16,
+        min_ce_length: u16,
+        max_ce_length: u16,
+    ) -> Message {
+        let header = MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x0c,
+            message_class: MessageClass::le_gap,
+            message_id: 0x22,
+        };
+        let payload = set_conn_timing_parameters {
+            min_interval,
+            max_interval,
+            latency,
+            timeout,
+            min_ce_length,
+            max_ce_length,
+        };
+        let payload = MessagePayload::cmd_le_gap_set_conn_timing_parameters(payload);
+        Message { header, payload }
+    }
+}
+
+impl From<&[u8]> for set_conn_timing_parameters {
+    fn from(data: &[u8]) -> set_conn_timing_parameters {
+        let mut cursor = Cursor::new(data);
+        set_conn_timing_parameters {
+            min_interval: cursor.get_u16_le(),
+            max_interval: cursor.get_u16_le(),
+            latency: cursor.get_u16_le(),
+            timeout: cursor.get_u16_le(),
+            min_ce_length: cursor.get_u16_le(),
+            max_ce_length: cursor.get_u16_le(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for set_conn_timing_parameters {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u16_le(self.min_interval);
+        bytes.put_u16_le(self.max_interval);
+        bytes.put_u16_le(self.latency);
+        bytes.put_u16_le(self.timeout);
+        bytes.put_u16_le(self.min_ce_length);
+        bytes.put_u16_le(self.max_ce_length);
+        bytes
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct set_data_channel_classification {
@@ -551,6 +788,43 @@ impl Into<Vec<u8>> for set_data_channel_classification {
     }
 }
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct set_discovery_extended_scan_response {
+    pub enable: u8,
+}
+
+impl set_discovery_extended_scan_response {
+    pub fn new(enable: u8) -> Message {
+        let header = MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x01,
+            message_class: MessageClass::le_gap,
+            message_id: 0x1c,
+        };
+        let payload = set_discovery_extended_scan_response { enable };
+        let payload = MessagePayload::cmd_le_gap_set_discovery_extended_scan_response(payload);
+        Message { header, payload }
+    }
+}
+
+impl From<&[u8]> for set_discovery_extended_scan_response {
+    fn from(data: &[u8]) -> set_discovery_extended_scan_response {
+        let mut cursor = Cursor::new(data);
+        set_discovery_extended_scan_response {
+            enable: cursor.get_u8(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for set_discovery_extended_scan_response {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u8(self.enable);
+        bytes
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct set_discovery_timing {
@@ -638,6 +912,49 @@ impl Into<Vec<u8>> for set_discovery_type {
     }
 }
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct set_long_advertising_data {
+    pub handle: u8,
+    pub packet_type: u8,
+}
+
+impl set_long_advertising_data {
+    pub fn new(handle: u8, packet_type: u8) -> Message {
+        let header = MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x02,
+            message_class: MessageClass::le_gap,
+            message_id: 0x20,
+        };
+        let payload = set_long_advertising_data {
+            handle,
+            packet_type,
+        };
+        let payload = MessagePayload::cmd_le_gap_set_long_advertising_data(payload);
+        Message { header, payload }
+    }
+}
+
+impl From<&[u8]> for set_long_advertising_data {
+    fn from(data: &[u8]) -> set_long_advertising_data {
+        let mut cursor = Cursor::new(data);
+        set_long_advertising_data {
+            handle: cursor.get_u8(),
+            packet_type: cursor.get_u8(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for set_long_advertising_data {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u8(self.handle);
+        bytes.put_u8(self.packet_type);
+        bytes
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct set_privacy_mode {
@@ -765,6 +1082,57 @@ impl Into<Vec<u8>> for start_discovery {
     }
 }
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct start_periodic_advertising {
+    pub handle: u8,
+    pub interval_min: u16,
+    pub interval_max: u16,
+    pub flags: u32,
+}
+
+impl start_periodic_advertising {
+    pub fn new(handle: u8, interval_min: u16, interval_max: u16, flags: u32) -> Message {
+        let header = MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x09,
+            message_class: MessageClass::le_gap,
+            message_id: 0x1d,
+        };
+        let payload = start_periodic_advertising {
+            handle,
+            interval_min,
+            interval_max,
+            flags,
+        };
+        let payload = MessagePayload::cmd_le_gap_start_periodic_advertising(payload);
+        Message { header, payload }
+    }
+}
+
+impl From<&[u8]> for start_periodic_advertising {
+    fn from(data: &[u8]) -> start_periodic_advertising {
+        let mut cursor = Cursor::new(data);
+        start_periodic_advertising {
+            handle: cursor.get_u8(),
+            interval_min: cursor.get_u16_le(),
+            interval_max: cursor.get_u16_le(),
+            flags: cursor.get_u32_le(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for start_periodic_advertising {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u8(self.handle);
+        bytes.put_u16_le(self.interval_min);
+        bytes.put_u16_le(self.interval_max);
+        bytes.put_u32_le(self.flags);
+        bytes
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct stop_advertising {
@@ -801,3 +1169,40 @@ impl Into<Vec<u8>> for stop_advertising {
         bytes
     }
 }
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct stop_periodic_advertising {
+    pub handle: u8,
+}
+
+impl stop_periodic_advertising {
+    pub fn new(handle: u8) -> Message {
+        let header = MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x01,
+            message_class: MessageClass::le_gap,
+            message_id: 0x1f,
+        };
+        let payload = stop_periodic_advertising { handle };
+        let payload = MessagePayload::cmd_le_gap_stop_periodic_advertising(payload);
+        Message { header, payload }
+    }
+}
+
+impl From<&[u8]> for stop_periodic_advertising {
+    fn from(data: &[u8]) -> stop_periodic_advertising {
+        let mut cursor = Cursor::new(data);
+        stop_periodic_advertising {
+            handle: cursor.get_u8(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for stop_periodic_advertising {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u8(self.handle);
+        bytes
+    }
+}
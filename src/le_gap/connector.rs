@@ -0,0 +1,211 @@
+use error::Error;
+use gatt::cmd::set_max_mtu;
+use le_connection::cmd::{close, set_parameters, set_phy};
+use le_connection::ConnectionParameters;
+use le_gap::cmd::{connect, end_procedure, set_conn_parameters};
+use le_gap::{AddressType, PhyType};
+use message::{Message, MessagePayload};
+use std::time::{Duration, Instant};
+
+const PHY_2M: u8 = 0x02;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectConfig {
+    pub initiating_phy: PhyType,
+    pub timeout: Duration,
+    pub parameters: Option<ConnectionParameters>, // Used when the connection is made
+    pub preferred_parameters: Option<ConnectionParameters>, // Requested once connected
+    pub phy_2m: bool,
+    pub max_mtu: Option<u16>,
+}
+
+impl Default for ConnectConfig {
+    fn default() -> ConnectConfig {
+        ConnectConfig {
+            initiating_phy: PhyType::phy_1m,
+            timeout: Duration::from_secs(5),
+            parameters: None,
+            preferred_parameters: None,
+            phy_2m: false,
+            max_mtu: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectEvent {
+    Command(Message), // Command to send
+    Connected(u8),
+    Failed(Error),
+    TimedOut,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum State {
+    Idle,
+    Connecting(Instant, Option<u8>), // Deadline and connection handle
+    Connected(u8),
+}
+
+// Connects to a peripheral as central. The stack keeps trying until the
+// peer is seen, so the attempt is abandoned with le_gap_end_procedure when it
+// takes longer than the timeout. Connections of abandoned attempts that open
+// anyway are closed.
+#[derive(Debug)]
+pub struct Connector {
+    config: ConnectConfig,
+    state: State,
+    unanswered: usize, // Abandoned attempts still awaiting their response
+    cancelled: Vec<u8>,
+}
+
+impl Connector {
+    pub fn new(config: ConnectConfig) -> Connector {
+        Connector {
+            config,
+            state: State::Idle,
+            unanswered: 0,
+            cancelled: Vec::new(),
+        }
+    }
+
+    pub fn config(&self) -> &ConnectConfig {
+        &self.config
+    }
+
+    pub fn is_connecting(&self) -> bool {
+        matches!(self.state, State::Connecting(..))
+    }
+
+    pub fn connection(&self) -> Option<u8> {
+        match self.state {
+            State::Connected(connection) => Some(connection),
+            _ => None,
+        }
+    }
+
+    // The maximum MTU is set before connecting as it is offered in the
+    // exchange that follows the connection.
+    pub fn connect(
+        &mut self,
+        address: [u8; 6],
+        address_type: AddressType,
+        now: Instant,
+    ) -> Vec<Message> {
+        if self.is_connecting() {
+            return Vec::new();
+        }
+        let mut commands = Vec::new();
+        if let Some(mtu) = self.config.max_mtu {
+            commands.push(set_max_mtu::new(mtu));
+        }
+        if let Some(ref x) = self.config.parameters {
+            commands.push(set_conn_parameters::new(
+                x.min_interval,
+                x.max_interval,
+                x.latency,
+                x.timeout,
+            ));
+        }
+        commands.push(connect::new(
+            address,
+            address_type,
+            self.config.initiating_phy.clone(),
+        ));
+        self.state = State::Connecting(now + self.config.timeout, None);
+        commands
+    }
+
+    // Ends the attempt once the timeout has passed.
+    pub fn poll(&mut self, now: Instant) -> Vec<ConnectEvent> {
+        match self.state {
+            State::Connecting(deadline, connection) if now >= deadline => {
+                match connection {
+                    Some(connection) => self.cancelled.push(connection),
+                    None => self.unanswered += 1,
+                }
+                self.state = State::Idle;
+                vec![
+                    ConnectEvent::Command(end_procedure::new()),
+                    ConnectEvent::TimedOut,
+                ]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn handle(&mut self, message: &Message) -> Vec<ConnectEvent> {
+        match message.payload {
+            // Responses come in the order the commands were sent.
+            MessagePayload::rsp_le_gap_connect(ref x) => {
+                if self.unanswered > 0 {
+                    self.unanswered -= 1;
+                    if x.result == Error::success {
+                        self.cancelled.push(x.connection);
+                    }
+                    return Vec::new();
+                }
+                if let State::Connecting(deadline, None) = self.state {
+                    self.cancelled.retain(|y| *y != x.connection);
+                    if x.result == Error::success {
+                        self.state = State::Connecting(deadline, Some(x.connection));
+                    } else {
+                        self.state = State::Idle;
+                        return vec![ConnectEvent::Failed(x.result.clone())];
+                    }
+                }
+                Vec::new()
+            }
+            MessagePayload::evt_le_connection_opened(ref x) => {
+                // Opened after the attempt timed out, so nobody expects it.
+                if self.cancelled.contains(&x.connection) {
+                    self.cancelled.retain(|y| *y != x.connection);
+                    return vec![ConnectEvent::Command(close::new(x.connection))];
+                }
+                match self.state {
+                    State::Connecting(_, Some(connection)) if connection == x.connection => {
+                        self.opened(connection)
+                    }
+                    _ => Vec::new(),
+                }
+            }
+            MessagePayload::evt_le_connection_closed(ref x) => {
+                self.cancelled.retain(|y| *y != x.connection);
+                match self.state {
+                    State::Connecting(_, Some(connection)) | State::Connected(connection)
+                        if connection == x.connection =>
+                    {
+                        let connecting = self.is_connecting();
+                        self.state = State::Idle;
+                        if connecting {
+                            vec![ConnectEvent::Failed(x.reason.clone())]
+                        } else {
+                            Vec::new()
+                        }
+                    }
+                    _ => Vec::new(),
+                }
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn opened(&mut self, connection: u8) -> Vec<ConnectEvent> {
+        self.state = State::Connected(connection);
+        let mut events = Vec::new();
+        if let Some(ref x) = self.config.preferred_parameters {
+            events.push(ConnectEvent::Command(set_parameters::new(
+                connection,
+                x.min_interval,
+                x.max_interval,
+                x.latency,
+                x.timeout,
+            )));
+        }
+        if self.config.phy_2m {
+            events.push(ConnectEvent::Command(set_phy::new(connection, PHY_2M)));
+        }
+        events.push(ConnectEvent::Connected(connection));
+        events
+    }
+}
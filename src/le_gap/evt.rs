@@ -1,5 +1,5 @@
 use bytes::{Buf, BufMut};
-use le_gap::AddressType;
+use le_gap::{AddressType, PacketType, PhyType};
 use num_traits::FromPrimitive;
 use std::io::{Cursor, Read};
 
@@ -26,6 +26,109 @@ impl Into<Vec<u8>> for adv_timeout {
     }
 }
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct extended_scan_response {
+    pub packet_type: PacketType,
+    pub address: [u8; 6],
+    pub address_type: AddressType,
+    pub bonding: u8,
+    pub primary_phy: PhyType,
+    pub secondary_phy: u8,
+    pub adv_sid: u8,
+    pub tx_power: i8,
+    pub rssi: i8,
+    pub channel: u8,
+    pub periodic_interval: u16,
+    pub data: Vec<u8>,
+}
+
+impl From<&[u8]> for extended_scan_response {
+    fn from(data: &[u8]) -> extended_scan_response {
+        let mut cursor = Cursor::new(data);
+        let packet_type = PacketType::from(cursor.get_u8());
+        let mut address: [u8; 6] = Default::default();
+        cursor
+            .read_exact(&mut address)
+            .expect("Failed to read bytes.");
+        address.reverse();
+        let address_type = FromPrimitive::from_u8(cursor.get_u8()).unwrap();
+        let bonding = cursor.get_u8();
+        let primary_phy = FromPrimitive::from_u8(cursor.get_u8()).unwrap();
+        let secondary_phy = cursor.get_u8();
+        let adv_sid = cursor.get_u8();
+        let tx_power = cursor.get_i8();
+        let rssi = cursor.get_i8();
+        let channel = cursor.get_u8();
+        let periodic_interval = cursor.get_u16_le();
+        let mut data = Vec::new();
+        cursor.get_u8();
+        cursor
+            .read_to_end(&mut data)
+            .expect("Failed to read bytes.");
+        extended_scan_response {
+            packet_type,
+            address,
+            address_type,
+            bonding,
+            primary_phy,
+            secondary_phy,
+            adv_sid,
+            tx_power,
+            rssi,
+            channel,
+            periodic_interval,
+            data,
+        }
+    }
+}
+
+impl Into<Vec<u8>> for extended_scan_response {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u8(self.packet_type.clone().into());
+        bytes.extend(self.address.iter().rev());
+        bytes.put_u8(self.address_type.clone() as u8);
+        bytes.put_u8(self.bonding);
+        bytes.put_u8(self.primary_phy.clone() as u8);
+        bytes.put_u8(self.secondary_phy);
+        bytes.put_u8(self.adv_sid);
+        bytes.put_i8(self.tx_power);
+        bytes.put_i8(self.rssi);
+        bytes.put_u8(self.channel);
+        bytes.put_u16_le(self.periodic_interval);
+        bytes.put_u8(self.data.len() as u8);
+        bytes.extend(self.data.iter());
+        bytes
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct periodic_advertising_status {
+    pub sid: u8,
+    pub status: u32,
+}
+
+impl From<&[u8]> for periodic_advertising_status {
+    fn from(data: &[u8]) -> periodic_advertising_status {
+        let mut cursor = Cursor::new(data);
+        periodic_advertising_status {
+            sid: cursor.get_u8(),
+            status: cursor.get_u32_le(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for periodic_advertising_status {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u8(self.sid);
+        bytes.put_u32_le(self.status);
+        bytes
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct scan_request {
@@ -70,7 +173,7 @@ impl Into<Vec<u8>> for scan_request {
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct scan_response {
     pub rssi: i8,
-    pub packet_type: u8,
+    pub packet_type: PacketType,
     pub address: [u8; 6],
     pub address_type: AddressType,
     pub bonding: u8,
@@ -81,7 +184,7 @@ impl From<&[u8]> for scan_response {
     fn from(data: &[u8]) -> scan_response {
         let mut cursor = Cursor::new(data);
         let rssi = cursor.get_i8();
-        let packet_type = cursor.get_u8();
+        let packet_type = PacketType::from(cursor.get_u8());
         let mut address: [u8; 6] = Default::default();
         cursor
             .read_exact(&mut address)
@@ -109,7 +212,7 @@ impl Into<Vec<u8>> for scan_response {
     fn into(self) -> Vec<u8> {
         let mut bytes = Vec::new();
         bytes.put_i8(self.rssi);
-        bytes.put_u8(self.packet_type);
+        bytes.put_u8(self.packet_type.clone().into());
         bytes.extend(self.address.iter().rev());
         bytes.put_u8(self.address_type.clone() as u8);
         bytes.put_u8(self.bonding);
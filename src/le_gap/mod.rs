@@ -1,9 +1,14 @@
+pub mod adv_data;
+pub mod advertiser;
 pub mod cmd;
+pub mod connector;
 pub mod evt;
 pub mod rsp;
+pub mod scanner;
 
 use message::{MessageClass, MessageHeader, MessagePayload, MessageType};
 use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
 
 use std::io::{Error, ErrorKind};
 
@@ -27,6 +32,15 @@ pub fn parse(header: &MessageHeader, buffer: &[u8]) -> Result<MessagePayload, Er
             rsp::clear_advertise_configuration::from(buffer),
         )),
 
+        MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x02,
+            message_class: MessageClass::le_gap,
+            message_id: 0x26,
+        } => Ok(MessagePayload::rsp_le_gap_clear_advertise_random_address(
+            rsp::clear_advertise_random_address::from(buffer),
+        )),
+
         MessageHeader {
             message_type: MessageType::command_response,
             payload_length: 0x03,
@@ -36,6 +50,15 @@ pub fn parse(header: &MessageHeader, buffer: &[u8]) -> Result<MessagePayload, Er
             buffer,
         ))),
 
+        MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x02,
+            message_class: MessageClass::le_gap,
+            message_id: 0x21,
+        } => Ok(MessagePayload::rsp_le_gap_enable_whitelisting(
+            rsp::enable_whitelisting::from(buffer),
+        )),
+
         MessageHeader {
             message_type: MessageType::command_response,
             payload_length: 0x02,
@@ -72,6 +95,15 @@ pub fn parse(header: &MessageHeader, buffer: &[u8]) -> Result<MessagePayload, Er
             rsp::set_advertise_phy::from(buffer),
         )),
 
+        MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x08,
+            message_class: MessageClass::le_gap,
+            message_id: 0x25,
+        } => Ok(MessagePayload::rsp_le_gap_set_advertise_random_address(
+            rsp::set_advertise_random_address::from(buffer),
+        )),
+
         MessageHeader {
             message_type: MessageType::command_response,
             payload_length: 0x02,
@@ -110,6 +142,24 @@ pub fn parse(header: &MessageHeader, buffer: &[u8]) -> Result<MessagePayload, Er
             rsp::set_conn_parameters::from(buffer),
         )),
 
+        MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x02,
+            message_class: MessageClass::le_gap,
+            message_id: 0x09,
+        } => Ok(MessagePayload::rsp_le_gap_set_conn_phy(
+            rsp::set_conn_phy::from(buffer),
+        )),
+
+        MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x02,
+            message_class: MessageClass::le_gap,
+            message_id: 0x22,
+        } => Ok(MessagePayload::rsp_le_gap_set_conn_timing_parameters(
+            rsp::set_conn_timing_parameters::from(buffer),
+        )),
+
         MessageHeader {
             message_type: MessageType::command_response,
             payload_length: 0x02,
@@ -119,6 +169,17 @@ pub fn parse(header: &MessageHeader, buffer: &[u8]) -> Result<MessagePayload, Er
             rsp::set_data_channel_classification::from(buffer),
         )),
 
+        MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x02,
+            message_class: MessageClass::le_gap,
+            message_id: 0x1c,
+        } => Ok(
+            MessagePayload::rsp_le_gap_set_discovery_extended_scan_response(
+                rsp::set_discovery_extended_scan_response::from(buffer),
+            ),
+        ),
+
         MessageHeader {
             message_type: MessageType::command_response,
             payload_length: 0x02,
@@ -137,6 +198,15 @@ pub fn parse(header: &MessageHeader, buffer: &[u8]) -> Result<MessagePayload, Er
             rsp::set_discovery_type::from(buffer),
         )),
 
+        MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x02,
+            message_class: MessageClass::le_gap,
+            message_id: 0x20,
+        } => Ok(MessagePayload::rsp_le_gap_set_long_advertising_data(
+            rsp::set_long_advertising_data::from(buffer),
+        )),
+
         MessageHeader {
             message_type: MessageType::command_response,
             payload_length: 0x02,
@@ -164,6 +234,15 @@ pub fn parse(header: &MessageHeader, buffer: &[u8]) -> Result<MessagePayload, Er
             rsp::start_discovery::from(buffer),
         )),
 
+        MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x02,
+            message_class: MessageClass::le_gap,
+            message_id: 0x1d,
+        } => Ok(MessagePayload::rsp_le_gap_start_periodic_advertising(
+            rsp::start_periodic_advertising::from(buffer),
+        )),
+
         MessageHeader {
             message_type: MessageType::command_response,
             payload_length: 0x02,
@@ -173,6 +252,15 @@ pub fn parse(header: &MessageHeader, buffer: &[u8]) -> Result<MessagePayload, Er
             rsp::stop_advertising::from(buffer),
         )),
 
+        MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x02,
+            message_class: MessageClass::le_gap,
+            message_id: 0x1f,
+        } => Ok(MessagePayload::rsp_le_gap_stop_periodic_advertising(
+            rsp::stop_periodic_advertising::from(buffer),
+        )),
+
         MessageHeader {
             message_type: MessageType::event,
             payload_length: 0x01,
@@ -182,6 +270,24 @@ pub fn parse(header: &MessageHeader, buffer: &[u8]) -> Result<MessagePayload, Er
             evt::adv_timeout::from(buffer),
         )),
 
+        MessageHeader {
+            message_type: MessageType::event,
+            payload_length: _,
+            message_class: MessageClass::le_gap,
+            message_id: 0x04,
+        } => Ok(MessagePayload::evt_le_gap_extended_scan_response(
+            evt::extended_scan_response::from(buffer),
+        )),
+
+        MessageHeader {
+            message_type: MessageType::event,
+            payload_length: 0x05,
+            message_class: MessageClass::le_gap,
+            message_id: 0x05,
+        } => Ok(MessagePayload::evt_le_gap_periodic_advertising_status(
+            evt::periodic_advertising_status::from(buffer),
+        )),
+
         MessageHeader {
             message_type: MessageType::event,
             payload_length: 0x09,
@@ -211,6 +317,7 @@ pub enum AddressType {
     random = 1,          // Random address
     public_identity = 2, // Public identity address resolved by stack
     random_identity = 3, // Random identity address resolved by stack
+    anonymous = 0xff,    // No address, only in extended advertising
 }
 
 #[allow(non_camel_case_types)]
@@ -220,6 +327,19 @@ pub enum AdvAddressType {
     non_resolvable = 1, // Use non resolvable address type, advertising mode must also be non-connectable
 }
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, FromPrimitive, PartialEq, PartialOrd)]
+pub enum AdvertisingEventType {
+    connectable_scannable_undirected = 0, // Connectable scannable undirected advertising
+    connectable_undirected = 1,           // Connectable undirected advertising
+    scannable_undirected = 2,             // Scannable undirected advertising
+    non_connectable_non_scannable_undirected = 3, // Non-connectable non-scannable undirected advertising
+    scan_response = 4,                            // Scan Response
+    reserved_5 = 5,
+    reserved_6 = 6,
+    reserved_7 = 7,
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, FromPrimitive, PartialEq, PartialOrd)]
 pub enum ConnectableMode {
@@ -230,6 +350,15 @@ pub enum ConnectableMode {
     connectable_non_scannable = 4, // Undirected connectable non-scannable. This mode can only be used in extended advertising PDUs.
 }
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, FromPrimitive, PartialEq, PartialOrd)]
+pub enum DataStatus {
+    complete = 0,   // Complete
+    incomplete = 1, // Incomplete, more data to come in new events
+    truncated = 2,  // Incomplete, data truncated, no more to come
+    reserved = 3,
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, FromPrimitive, PartialEq, PartialOrd)]
 pub enum DiscoverMode {
@@ -248,6 +377,60 @@ pub enum DiscoverableMode {
     user_data = 4, // Send advertising and/or scan response data defined by the user using le_gap_bt5_set_adv_data. The limited/general discoverable flags are defined by the user.
 }
 
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct PacketType {
+    pub event_type: AdvertisingEventType,
+    pub data_status: DataStatus,
+    pub extended: bool,
+    pub reserved: u8, // Bits 3 and 4, kept so the byte converts back unchanged
+}
+
+impl PacketType {
+    pub fn is_connectable(&self) -> bool {
+        matches!(
+            self.event_type,
+            AdvertisingEventType::connectable_scannable_undirected
+                | AdvertisingEventType::connectable_undirected
+        )
+    }
+
+    pub fn is_scannable(&self) -> bool {
+        matches!(
+            self.event_type,
+            AdvertisingEventType::connectable_scannable_undirected
+                | AdvertisingEventType::scannable_undirected
+        )
+    }
+
+    pub fn is_scan_response(&self) -> bool {
+        self.event_type == AdvertisingEventType::scan_response
+    }
+}
+
+// Reserved values have variants of their own, so every byte converts.
+impl From<u8> for PacketType {
+    fn from(data: u8) -> PacketType {
+        PacketType {
+            event_type: FromPrimitive::from_u8(data & 0x07).unwrap(),
+            data_status: FromPrimitive::from_u8((data >> 5) & 0x03).unwrap(),
+            extended: data & 0x80 != 0,
+            reserved: (data >> 3) & 0x03,
+        }
+    }
+}
+
+impl Into<u8> for PacketType {
+    fn into(self) -> u8 {
+        let mut data = self.event_type as u8;
+        data |= (self.reserved & 0x03) << 3;
+        data |= (self.data_status as u8) << 5;
+        if self.extended {
+            data |= 0x80;
+        }
+        data
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, FromPrimitive, PartialEq, PartialOrd)]
 pub enum PhyType {
@@ -1,7 +1,7 @@
 use bytes::{Buf, BufMut};
 use error::Error;
 use num_traits::FromPrimitive;
-use std::io::Cursor;
+use std::io::{Cursor, Read};
 
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
@@ -49,6 +49,29 @@ impl Into<Vec<u8>> for clear_advertise_configuration {
     }
 }
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct clear_advertise_random_address {
+    pub result: Error,
+}
+
+impl From<&[u8]> for clear_advertise_random_address {
+    fn from(data: &[u8]) -> clear_advertise_random_address {
+        let mut cursor = Cursor::new(data);
+        clear_advertise_random_address {
+            result: FromPrimitive::from_u16(cursor.get_u16_le()).unwrap(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for clear_advertise_random_address {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u16_le(self.result.clone() as u16);
+        bytes
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct connect {
@@ -75,6 +98,29 @@ impl Into<Vec<u8>> for connect {
     }
 }
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct enable_whitelisting {
+    pub result: Error,
+}
+
+impl From<&[u8]> for enable_whitelisting {
+    fn from(data: &[u8]) -> enable_whitelisting {
+        let mut cursor = Cursor::new(data);
+        enable_whitelisting {
+            result: FromPrimitive::from_u16(cursor.get_u16_le()).unwrap(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for enable_whitelisting {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u16_le(self.result.clone() as u16);
+        bytes
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct end_procedure {
@@ -167,6 +213,38 @@ impl Into<Vec<u8>> for set_advertise_phy {
     }
 }
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct set_advertise_random_address {
+    pub result: Error,
+    pub address_out: [u8; 6],
+}
+
+impl From<&[u8]> for set_advertise_random_address {
+    fn from(data: &[u8]) -> set_advertise_random_address {
+        let mut cursor = Cursor::new(data);
+        let result = FromPrimitive::from_u16(cursor.get_u16_le()).unwrap();
+        let mut address_out: [u8; 6] = Default::default();
+        cursor
+            .read_exact(&mut address_out)
+            .expect("Failed to read bytes.");
+        address_out.reverse();
+        set_advertise_random_address {
+            result,
+            address_out,
+        }
+    }
+}
+
+impl Into<Vec<u8>> for set_advertise_random_address {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u16_le(self.result.clone() as u16);
+        bytes.extend(self.address_out.iter().rev());
+        bytes
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct set_advertise_report_scan_request {
@@ -259,6 +337,52 @@ impl Into<Vec<u8>> for set_conn_parameters {
     }
 }
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct set_conn_phy {
+    pub result: Error,
+}
+
+impl From<&[u8]> for set_conn_phy {
+    fn from(data: &[u8]) -> set_conn_phy {
+        let mut cursor = Cursor::new(data);
+        set_conn_phy {
+            result: FromPrimitive::from_u16(cursor.get_u16_le()).unwrap(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for set_conn_phy {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u16_le(self.result.clone() as u16);
+        bytes
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct set_conn_timing_parameters {
+    pub result: Error,
+}
+
+impl From<&[u8]> for set_conn_timing_parameters {
+    fn from(data: &[u8]) -> set_conn_timing_parameters {
+        let mut cursor = Cursor::new(data);
+        set_conn_timing_parameters {
+            result: FromPrimitive::from_u16(cursor.get_u16_le()).unwrap(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for set_conn_timing_parameters {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u16_le(self.result.clone() as u16);
+        bytes
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct set_data_channel_classification {
@@ -282,6 +406,29 @@ impl Into<Vec<u8>> for set_data_channel_classification {
     }
 }
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct set_discovery_extended_scan_response {
+    pub result: Error,
+}
+
+impl From<&[u8]> for set_discovery_extended_scan_response {
+    fn from(data: &[u8]) -> set_discovery_extended_scan_response {
+        let mut cursor = Cursor::new(data);
+        set_discovery_extended_scan_response {
+            result: FromPrimitive::from_u16(cursor.get_u16_le()).unwrap(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for set_discovery_extended_scan_response {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u16_le(self.result.clone() as u16);
+        bytes
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct set_discovery_timing {
@@ -328,6 +475,29 @@ impl Into<Vec<u8>> for set_discovery_type {
     }
 }
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct set_long_advertising_data {
+    pub result: Error,
+}
+
+impl From<&[u8]> for set_long_advertising_data {
+    fn from(data: &[u8]) -> set_long_advertising_data {
+        let mut cursor = Cursor::new(data);
+        set_long_advertising_data {
+            result: FromPrimitive::from_u16(cursor.get_u16_le()).unwrap(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for set_long_advertising_data {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u16_le(self.result.clone() as u16);
+        bytes
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct set_privacy_mode {
@@ -397,6 +567,29 @@ impl Into<Vec<u8>> for start_discovery {
     }
 }
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct start_periodic_advertising {
+    pub result: Error,
+}
+
+impl From<&[u8]> for start_periodic_advertising {
+    fn from(data: &[u8]) -> start_periodic_advertising {
+        let mut cursor = Cursor::new(data);
+        start_periodic_advertising {
+            result: FromPrimitive::from_u16(cursor.get_u16_le()).unwrap(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for start_periodic_advertising {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u16_le(self.result.clone() as u16);
+        bytes
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct stop_advertising {
@@ -419,3 +612,26 @@ impl Into<Vec<u8>> for stop_advertising {
         bytes
     }
 }
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct stop_periodic_advertising {
+    pub result: Error,
+}
+
+impl From<&[u8]> for stop_periodic_advertising {
+    fn from(data: &[u8]) -> stop_periodic_advertising {
+        let mut cursor = Cursor::new(data);
+        stop_periodic_advertising {
+            result: FromPrimitive::from_u16(cursor.get_u16_le()).unwrap(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for stop_periodic_advertising {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u16_le(self.result.clone() as u16);
+        bytes
+    }
+}
@@ -0,0 +1,319 @@
+use le_gap::adv_data;
+use le_gap::cmd::{
+    end_procedure, set_discovery_extended_scan_response, set_discovery_timing, set_discovery_type,
+    start_discovery,
+};
+use le_gap::{AddressType, DataStatus, DiscoverMode, PacketType, PhyType};
+use message::{Message, MessagePayload};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+const TX_POWER_NOT_AVAILABLE: i8 = 127;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScannerConfig {
+    pub phy: PhyType,
+    pub mode: DiscoverMode,
+    pub active: bool,
+    pub extended: bool, // Report with evt_le_gap_extended_scan_response, required for coded PHY
+    pub scan_interval: u16, // Units of 0.625 ms
+    pub scan_window: u16, // Units of 0.625 ms
+    pub rssi_history: usize,
+    pub rssi_smoothing: f32, // Weight of a new sample in the moving average, 0.0 to 1.0
+    pub max_age: Duration,
+}
+
+impl Default for ScannerConfig {
+    fn default() -> ScannerConfig {
+        ScannerConfig {
+            phy: PhyType::phy_1m,
+            mode: DiscoverMode::generic,
+            active: true,
+            extended: false,
+            scan_interval: 0x10,
+            scan_window: 0x10,
+            rssi_history: 16,
+            rssi_smoothing: 0.25,
+            max_age: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScanFilter {
+    pub name_prefix: Option<String>,
+    pub service_uuid: Option<Vec<u8>>,
+    pub manufacturer_id: Option<u16>,
+    pub min_rssi: Option<i8>,
+}
+
+impl ScanFilter {
+    pub fn matches(&self, device: &Device) -> bool {
+        if let Some(ref prefix) = self.name_prefix {
+            match device.name() {
+                Some(ref name) if name.starts_with(prefix.as_str()) => (),
+                _ => return false,
+            }
+        }
+        if let Some(ref uuid) = self.service_uuid {
+            if !device.service_uuids().contains(uuid) {
+                return false;
+            }
+        }
+        if let Some(id) = self.manufacturer_id {
+            if !device.manufacturer_data().iter().any(|x| x.0 == id) {
+                return false;
+            }
+        }
+        if let Some(rssi) = self.min_rssi {
+            if device.rssi < rssi {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Device {
+    pub address: [u8; 6],
+    pub address_type: AddressType,
+    pub bonding: u8,
+    pub adv_data: Vec<u8>,
+    pub scan_rsp_data: Vec<u8>,
+    pub rssi: i8,
+    pub tx_power: Option<i8>,
+    pub smoothed_rssi: f32,
+    pub rssi_history: VecDeque<(Instant, i8)>,
+    pub first_seen: Instant,
+    pub last_seen: Instant,
+}
+
+impl Device {
+    // Advertising data followed by scan response data.
+    pub fn data(&self) -> Vec<u8> {
+        let mut data = self.adv_data.clone();
+        data.extend(self.scan_rsp_data.iter());
+        data
+    }
+
+    pub fn name(&self) -> Option<String> {
+        adv_data::local_name(&self.data())
+    }
+
+    pub fn service_uuids(&self) -> Vec<Vec<u8>> {
+        adv_data::service_uuids(&self.data())
+    }
+
+    pub fn manufacturer_data(&self) -> Vec<(u16, Vec<u8>)> {
+        adv_data::manufacturer_data(&self.data())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanEvent {
+    DeviceFound(Device),
+    DeviceUpdated(Device),
+    DeviceLost(Device),
+}
+
+#[derive(Debug)]
+pub struct Scanner {
+    config: ScannerConfig,
+    filter: ScanFilter,
+    devices: HashMap<[u8; 6], Device>,
+    reported: HashSet<[u8; 6]>,
+    incomplete: HashSet<([u8; 6], bool)>, // Address and whether a scan response
+}
+
+struct Report<'a> {
+    address: [u8; 6],
+    address_type: &'a AddressType,
+    bonding: u8,
+    packet_type: &'a PacketType,
+    rssi: i8,
+    tx_power: Option<i8>,
+    data: &'a [u8],
+}
+
+impl Scanner {
+    pub fn new(config: ScannerConfig, filter: ScanFilter) -> Scanner {
+        Scanner {
+            config,
+            filter,
+            devices: HashMap::new(),
+            reported: HashSet::new(),
+            incomplete: HashSet::new(),
+        }
+    }
+
+    pub fn config(&self) -> &ScannerConfig {
+        &self.config
+    }
+
+    pub fn filter(&self) -> &ScanFilter {
+        &self.filter
+    }
+
+    pub fn set_filter(&mut self, filter: ScanFilter) {
+        self.filter = filter;
+        self.reported.clear();
+    }
+
+    // Commands to send, in order, to start scanning.
+    pub fn start(&self) -> Vec<Message> {
+        let phys = self.config.phy.clone() as u8;
+        let mut commands = Vec::new();
+        if self.config.extended {
+            commands.push(set_discovery_extended_scan_response::new(1));
+        }
+        commands.push(set_discovery_type::new(phys, self.config.active as u8));
+        commands.push(set_discovery_timing::new(
+            phys,
+            self.config.scan_interval,
+            self.config.scan_window,
+        ));
+        commands.push(start_discovery::new(
+            self.config.phy.clone(),
+            self.config.mode.clone(),
+        ));
+        commands
+    }
+
+    pub fn stop(&self) -> Vec<Message> {
+        vec![end_procedure::new()]
+    }
+
+    pub fn handle(&mut self, message: &Message, now: Instant) -> Option<ScanEvent> {
+        match message.payload {
+            MessagePayload::evt_le_gap_scan_response(ref x) => self.update(
+                &Report {
+                    address: x.address,
+                    address_type: &x.address_type,
+                    bonding: x.bonding,
+                    packet_type: &x.packet_type,
+                    rssi: x.rssi,
+                    tx_power: None,
+                    data: &x.data,
+                },
+                now,
+            ),
+            MessagePayload::evt_le_gap_extended_scan_response(ref x) => self.update(
+                &Report {
+                    address: x.address,
+                    address_type: &x.address_type,
+                    bonding: x.bonding,
+                    packet_type: &x.packet_type,
+                    rssi: x.rssi,
+                    tx_power: if x.tx_power == TX_POWER_NOT_AVAILABLE {
+                        None
+                    } else {
+                        Some(x.tx_power)
+                    },
+                    data: &x.data,
+                },
+                now,
+            ),
+            _ => None,
+        }
+    }
+
+    // Removes devices not seen for longer than the configured maximum age.
+    pub fn expire(&mut self, now: Instant) -> Vec<ScanEvent> {
+        let max_age = self.config.max_age;
+        let expired: Vec<[u8; 6]> = self
+            .devices
+            .values()
+            .filter(|x| now.duration_since(x.last_seen) > max_age)
+            .map(|x| x.address)
+            .collect();
+        let mut events = Vec::new();
+        for address in expired {
+            let device = self.devices.remove(&address).unwrap();
+            self.incomplete.retain(|x| x.0 != address);
+            if self.reported.remove(&address) {
+                events.push(ScanEvent::DeviceLost(device));
+            }
+        }
+        events
+    }
+
+    // Devices that pass the current filter.
+    pub fn devices(&self) -> Vec<&Device> {
+        self.devices
+            .values()
+            .filter(|x| self.filter.matches(x))
+            .collect()
+    }
+
+    pub fn device(&self, address: &[u8; 6]) -> Option<&Device> {
+        self.devices.get(address)
+    }
+
+    pub fn clear(&mut self) {
+        self.devices.clear();
+        self.reported.clear();
+        self.incomplete.clear();
+    }
+
+    fn update(&mut self, report: &Report, now: Instant) -> Option<ScanEvent> {
+        // Anonymous advertisers all report the zero address, so there is no
+        // telling them apart.
+        if *report.address_type == AddressType::anonymous {
+            return None;
+        }
+        let config = &self.config;
+        let device = self.devices.entry(report.address).or_insert(Device {
+            address: report.address,
+            address_type: report.address_type.clone(),
+            bonding: report.bonding,
+            adv_data: Vec::new(),
+            scan_rsp_data: Vec::new(),
+            rssi: report.rssi,
+            tx_power: None,
+            smoothed_rssi: report.rssi as f32,
+            rssi_history: VecDeque::new(),
+            first_seen: now,
+            last_seen: now,
+        });
+
+        // Extended advertising data may be split across several reports,
+        // which can interleave with those of the scan response.
+        let scan_response = report.packet_type.is_scan_response();
+        let buffer = if scan_response {
+            &mut device.scan_rsp_data
+        } else {
+            &mut device.adv_data
+        };
+        let key = (report.address, scan_response);
+        if !self.incomplete.remove(&key) {
+            buffer.clear();
+        }
+        buffer.extend_from_slice(report.data);
+        if report.packet_type.data_status == DataStatus::incomplete {
+            self.incomplete.insert(key);
+        }
+        if report.tx_power.is_some() {
+            device.tx_power = report.tx_power;
+        }
+
+        device.address_type = report.address_type.clone();
+        device.bonding = report.bonding;
+        device.rssi = report.rssi;
+        device.smoothed_rssi += config.rssi_smoothing * (report.rssi as f32 - device.smoothed_rssi);
+        device.rssi_history.push_back((now, report.rssi));
+        while device.rssi_history.len() > config.rssi_history {
+            device.rssi_history.pop_front();
+        }
+        device.last_seen = now;
+
+        if !self.filter.matches(device) {
+            return None;
+        }
+        if self.reported.insert(report.address) {
+            Some(ScanEvent::DeviceFound(device.clone()))
+        } else {
+            Some(ScanEvent::DeviceUpdated(device.clone()))
+        }
+    }
+}
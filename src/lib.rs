@@ -10,11 +10,13 @@ pub mod flash;
 pub mod gatt;
 pub mod gatt_server;
 pub mod hardware;
+mod hex;
 pub mod le_connection;
 pub mod le_gap;
 pub mod message;
 pub mod parser;
 pub mod sm;
+pub mod sync;
 pub mod system;
 pub mod test;
 pub mod user;
@@ -34,6 +36,69 @@ mod tests {
         }
     }
 
+    // Parses a message from its bytes as received from the NCP.
+    fn parse_bytes(bytes: &[u8]) -> ::message::Message {
+        let (tx, rx) = spmc::channel();
+        for x in bytes {
+            tx.send(*x).unwrap();
+        }
+        parser::parse_next_message(&rx).expect("Failed parsing message.")
+    }
+
+    // Builds an event as sent by the NCP. Parsing it back checks that the
+    // class and id match the payload.
+    fn event(
+        message_class: ::message::MessageClass,
+        message_id: u8,
+        payload: ::message::MessagePayload,
+    ) -> ::message::Message {
+        message(
+            ::message::MessageType::event,
+            message_class,
+            message_id,
+            payload,
+        )
+    }
+
+    fn response(
+        message_class: ::message::MessageClass,
+        message_id: u8,
+        payload: ::message::MessagePayload,
+    ) -> ::message::Message {
+        message(
+            ::message::MessageType::command_response,
+            message_class,
+            message_id,
+            payload,
+        )
+    }
+
+    // A path in the temporary directory unique to this test run.
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("{}_{}", name, std::process::id()))
+    }
+
+    fn message(
+        message_type: ::message::MessageType,
+        message_class: ::message::MessageClass,
+        message_id: u8,
+        payload: ::message::MessagePayload,
+    ) -> ::message::Message {
+        let bytes: Vec<u8> = payload.clone().into();
+        let message = ::message::Message {
+            header: ::message::MessageHeader {
+                message_type,
+                payload_length: bytes.len() as u8,
+                message_class,
+                message_id,
+            },
+            payload,
+        };
+        let bytes: Vec<u8> = message.clone().into();
+        assert_eq!(parse_bytes(&bytes), message);
+        message
+    }
+
     #[test]
     fn message_header_from_bytes() {
         use message::{MessageClass, MessageHeader, MessageType};
@@ -141,4 +206,2266 @@ mod tests {
         let cmd: Vec<u8> = hello::new().into();
         assert_eq!(cmd, vec![0x20, 0x00, 0x01, 0x00]);
     }
+
+    #[test]
+    fn scanner_merges_scan_response_and_filters_by_name() {
+        use le_gap::evt::scan_response;
+        use le_gap::scanner::{ScanEvent, ScanFilter, Scanner, ScannerConfig};
+        use le_gap::AddressType;
+        use message::{MessageClass, MessagePayload};
+        use std::time::Instant;
+
+        let report = |packet_type: u8, data: Vec<u8>| {
+            event(
+                MessageClass::le_gap,
+                0x00,
+                MessagePayload::evt_le_gap_scan_response(scan_response {
+                    rssi: -60,
+                    packet_type: packet_type.into(),
+                    address: [0x00, 0x0b, 0x57, 0x01, 0x02, 0x03],
+                    address_type: AddressType::public,
+                    bonding: 0xff,
+                    data,
+                }),
+            )
+        };
+        let filter = ScanFilter {
+            name_prefix: Some(String::from("Thermo")),
+            ..Default::default()
+        };
+        let mut scanner = Scanner::new(ScannerConfig::default(), filter);
+        let now = Instant::now();
+
+        let adv = report(0x00, vec![0x02, 0x01, 0x06, 0x03, 0x03, 0x0f, 0x18]);
+        assert_eq!(scanner.handle(&adv, now), None);
+
+        let rsp = report(0x04, vec![0x07, 0x09, b'T', b'h', b'e', b'r', b'm', b'o']);
+        match scanner.handle(&rsp, now) {
+            Some(ScanEvent::DeviceFound(device)) => {
+                assert_eq!(device.name(), Some(String::from("Thermo")));
+                assert_eq!(device.service_uuids(), vec![vec![0x18, 0x0f]]);
+            }
+            x => panic!("Unexpected scan event {:?}", x),
+        }
+        assert_eq!(scanner.devices().len(), 1);
+    }
+
+    #[test]
+    fn evt_le_gap_extended_scan_response_from_bytes() {
+        use le_gap::evt::extended_scan_response;
+        use le_gap::{AddressType, AdvertisingEventType, DataStatus, PacketType, PhyType};
+
+        let data = [
+            0x80, 0x03, 0x02, 0x01, 0x57, 0x0b, 0x00, 0x00, 0xff, 0x04, 0x04, 0x01, 0x08, 0xc4,
+            0x25, 0x00, 0x00, 0x02, 0x01, 0x06,
+        ];
+        let actual = extended_scan_response::from(&data[..]);
+        let expected = extended_scan_response {
+            packet_type: PacketType {
+                event_type: AdvertisingEventType::connectable_scannable_undirected,
+                data_status: DataStatus::complete,
+                extended: true,
+                reserved: 0,
+            },
+            address: [0x00, 0x0b, 0x57, 0x01, 0x02, 0x03],
+            address_type: AddressType::public,
+            bonding: 0xff,
+            primary_phy: PhyType::phy_coded,
+            secondary_phy: 0x04,
+            adv_sid: 0x01,
+            tx_power: 8,
+            rssi: -60,
+            channel: 37,
+            periodic_interval: 0,
+            data: vec![0x01, 0x06],
+        };
+        assert_eq!(actual, expected);
+        assert!(actual.packet_type.is_connectable());
+
+        let bytes: Vec<u8> = actual.into();
+        assert_eq!(bytes, data.to_vec());
+
+        let packet_type = PacketType::from(0x88);
+        assert_eq!(packet_type.reserved, 0x01);
+        let byte: u8 = packet_type.into();
+        assert_eq!(byte, 0x88);
+    }
+
+    #[test]
+    fn advertiser_applies_configuration_and_tracks_timeout() {
+        use le_gap::advertiser::Advertiser;
+        use le_gap::advertiser::{AdvertisingConfig, AdvertisingEvent, AdvertisingState};
+        use le_gap::evt::adv_timeout;
+        use message::{MessageClass, MessagePayload};
+
+        let mut advertiser = Advertiser::new(2);
+        let config = AdvertisingConfig {
+            adv_data: Some(vec![0x02, 0x01, 0x06]),
+            ..Default::default()
+        };
+        let handle = advertiser.create(config.clone()).unwrap();
+        assert_eq!(advertiser.create(config.clone()), Some(1));
+        assert_eq!(advertiser.create(config), None);
+
+        let commands = advertiser.start(handle);
+        let ids: Vec<u8> = commands.iter().map(|x| x.header.message_id).collect();
+        assert_eq!(ids, vec![0x0e, 0x0f, 0x11, 0x13, 0x10, 0x0c, 0x14]);
+        let timing: Vec<u8> = commands[0].clone().into();
+        assert_eq!(
+            timing,
+            vec![
+                0x20, 0x0c, 0x03, 0x0e, 0x00, 0xa0, 0x00, 0x00, 0x00, 0x40, 0x01, 0x00, 0x00, 0x00,
+                0x00, 0x00
+            ]
+        );
+        let data: Vec<u8> = commands[5].clone().into();
+        assert_eq!(
+            data,
+            vec![0x20, 0x06, 0x03, 0x0c, 0x00, 0x00, 0x03, 0x02, 0x01, 0x06]
+        );
+
+        let timeout = event(
+            MessageClass::le_gap,
+            0x01,
+            MessagePayload::evt_le_gap_adv_timeout(adv_timeout { handle }),
+        );
+        assert_eq!(
+            advertiser.handle(&timeout),
+            Some(AdvertisingEvent {
+                handle,
+                state: AdvertisingState::TimedOut,
+            })
+        );
+    }
+
+    #[test]
+    fn message_evt_sync_data_from_bytes() {
+        use le_gap::DataStatus;
+        use message::MessagePayload;
+        use parser::parse_next_message;
+        use sync::evt::data;
+
+        let (tx, rx) = spmc::channel();
+        let bytes = [
+            0xa0, 0x08, 0x42, 0x02, 0x00, 0x7f, 0xb5, 0x00, 0x03, 0x02, 0x01, 0x06,
+        ];
+        for x in &bytes {
+            tx.send(*x).unwrap();
+        }
+        let actual = parse_next_message(&rx).expect("Failed parsing message.");
+        let expected = MessagePayload::evt_sync_data(data {
+            sync: 0,
+            tx_power: 127,
+            rssi: -75,
+            data_status: DataStatus::complete,
+            data: vec![0x02, 0x01, 0x06],
+        });
+        assert_eq!(actual.payload, expected);
+
+        let msg: Vec<u8> = actual.into();
+        assert_eq!(msg, bytes.to_vec());
+    }
+
+    #[test]
+    fn gatt_discovery_builds_attribute_tree() {
+        use error::Error;
+        use gatt::discovery::{Discovery, DiscoveryEvent};
+        use gatt::{evt, CharacteristicProperties};
+        use message::{MessageClass, MessagePayload};
+
+        let completed = event(
+            MessageClass::gatt,
+            0x06,
+            MessagePayload::evt_gatt_procedure_completed(evt::procedure_completed {
+                connection: 1,
+                result: Error::success,
+            }),
+        );
+
+        let mut discovery = Discovery::new(1);
+        match discovery.start() {
+            Some(DiscoveryEvent::Command(x)) => assert_eq!(x.header.message_id, 0x01),
+            x => panic!("Unexpected discovery event {:?}", x),
+        }
+        discovery.handle(&event(
+            MessageClass::gatt,
+            0x01,
+            MessagePayload::evt_gatt_service(evt::service {
+                connection: 1,
+                service: 0x0010_0001,
+                uuid: vec![0x18, 0x0f],
+            }),
+        ));
+        match discovery.handle(&completed) {
+            Some(DiscoveryEvent::Command(x)) => assert_eq!(x.header.message_id, 0x03),
+            x => panic!("Unexpected discovery event {:?}", x),
+        }
+        discovery.handle(&event(
+            MessageClass::gatt,
+            0x02,
+            MessagePayload::evt_gatt_characteristic(evt::characteristic {
+                connection: 1,
+                characteristic: 0x0003,
+                properties: CharacteristicProperties(0x12),
+                uuid: vec![0x2a, 0x19],
+            }),
+        ));
+        match discovery.handle(&completed) {
+            Some(DiscoveryEvent::Command(x)) => assert_eq!(x.header.message_id, 0x06),
+            x => panic!("Unexpected discovery event {:?}", x),
+        }
+        discovery.handle(&event(
+            MessageClass::gatt,
+            0x03,
+            MessagePayload::evt_gatt_descriptor(evt::descriptor {
+                connection: 1,
+                descriptor: 0x0004,
+                uuid: vec![0x29, 0x02],
+            }),
+        ));
+        let services = match discovery.handle(&completed) {
+            Some(DiscoveryEvent::Complete(x)) => x,
+            x => panic!("Unexpected discovery event {:?}", x),
+        };
+
+        assert_eq!(services.len(), 1);
+        assert_eq!(services[0].start_handle(), 0x0001);
+        assert_eq!(services[0].end_handle(), 0x0010);
+        let characteristic = services[0].characteristic(&[0x2a, 0x19]).unwrap();
+        assert!(characteristic
+            .properties
+            .contains(CharacteristicProperties::READ | CharacteristicProperties::NOTIFY));
+        assert_eq!(characteristic.descriptors[0].handle, 0x0004);
+    }
+
+    #[test]
+    fn gatt_cache_round_trips_and_invalidates_on_service_changed() {
+        use gatt::cache::{CacheEvent, DiscoveryCache};
+        use gatt::discovery::{Characteristic, Service};
+        use gatt::{evt, AttOpcode, CharacteristicProperties};
+        use le_connection::{self, Role};
+        use le_gap::AddressType;
+        use message::{MessageClass, MessagePayload};
+
+        let address = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+        let services = vec![Service {
+            handle: 0x0004_0001,
+            uuid: vec![0x18, 0x01],
+            characteristics: vec![Characteristic {
+                handle: 0x0003,
+                properties: CharacteristicProperties::INDICATE,
+                uuid: vec![0x2a, 0x05],
+                descriptors: Vec::new(),
+            }],
+        }];
+
+        let mut cache = DiscoveryCache::new();
+        cache.insert(address, Some([0xaa; 16]), services.clone());
+        let path = temp_path("bgapi_gatt_cache_test");
+        cache.save(&path).unwrap();
+        let mut cache = DiscoveryCache::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(cache.get(&address, Some(&[0xaa; 16])), Some(&services[..]));
+        assert_eq!(cache.get(&address, Some(&[0xbb; 16])), None);
+
+        cache.handle(&event(
+            MessageClass::le_connection,
+            0x00,
+            MessagePayload::evt_le_connection_opened(le_connection::evt::opened {
+                address,
+                address_type: AddressType::public,
+                master: Role::master,
+                connection: 1,
+                bonding: 0,
+                advertiser: 0xff,
+            }),
+        ));
+        assert_eq!(cache.services(1), Some(&services[..]));
+        let indication = event(
+            MessageClass::gatt,
+            0x04,
+            MessagePayload::evt_gatt_characteristic_value(evt::characteristic_value {
+                connection: 1,
+                characteristic: 0x0003,
+                att_opcode: AttOpcode::handle_value_indication,
+                offset: 0,
+                value: vec![0x01, 0x00, 0xff, 0xff],
+            }),
+        );
+        assert_eq!(
+            cache.handle(&indication),
+            Some(CacheEvent::Invalidated(address))
+        );
+        assert_eq!(cache.services(1), None);
+    }
+
+    #[test]
+    fn gatt_long_value_reassembles_reads_and_cancels_failed_writes() {
+        use error::Error;
+        use gatt::long_value::{LongValue, LongValueEvent};
+        use gatt::{evt, AttOpcode};
+        use message::{MessageClass, MessagePayload};
+
+        let completed = |result: Error| {
+            event(
+                MessageClass::gatt,
+                0x06,
+                MessagePayload::evt_gatt_procedure_completed(evt::procedure_completed {
+                    connection: 1,
+                    result,
+                }),
+            )
+        };
+        let fragment = |offset: u16, value: Vec<u8>| {
+            event(
+                MessageClass::gatt,
+                0x04,
+                MessagePayload::evt_gatt_characteristic_value(evt::characteristic_value {
+                    connection: 1,
+                    characteristic: 0x0010,
+                    att_opcode: AttOpcode::read_blob_response,
+                    offset,
+                    value,
+                }),
+            )
+        };
+
+        let mut long_value = LongValue::new(1);
+        match long_value.read_long(0x0010) {
+            Some(LongValueEvent::Command(x)) => assert_eq!(x.header.message_id, 0x12),
+            x => panic!("Unexpected long value event {:?}", x),
+        }
+        long_value.handle(&fragment(0, vec![0xaa; 22]));
+        match long_value.handle(&completed(Error::success)) {
+            Some(LongValueEvent::Command(x)) => assert_eq!(x.header.message_id, 0x12),
+            x => panic!("Unexpected long value event {:?}", x),
+        }
+        long_value.handle(&fragment(22, vec![0xbb; 8]));
+        match long_value.handle(&completed(Error::success)) {
+            Some(LongValueEvent::ReadComplete(x)) => {
+                assert_eq!(x.len(), 30);
+                assert_eq!(x[22], 0xbb);
+            }
+            x => panic!("Unexpected long value event {:?}", x),
+        }
+
+        match long_value.write_long(0x0010, vec![0xcc; 30]) {
+            Some(LongValueEvent::Command(x)) => assert_eq!(x.header.message_id, 0x0b),
+            x => panic!("Unexpected long value event {:?}", x),
+        }
+        match long_value.handle(&completed(Error::invalid_att_length)) {
+            Some(LongValueEvent::Command(x)) => assert_eq!(x.header.message_id, 0x0c),
+            x => panic!("Unexpected long value event {:?}", x),
+        }
+        assert_eq!(
+            long_value.handle(&completed(Error::success)),
+            Some(LongValueEvent::Failed(Error::invalid_att_length))
+        );
+        assert!(!long_value.is_busy());
+
+        // Offsets would not fit in 16 bits.
+        assert_eq!(
+            long_value.write_long(0x0010, vec![0xcc; 0x10000]),
+            Some(LongValueEvent::Failed(Error::invalid_att_length))
+        );
+        assert!(!long_value.is_busy());
+    }
+
+    #[test]
+    fn gatt_reliable_write_cancels_on_echo_mismatch() {
+        use error::Error;
+        use gatt::long_value::{Fragment, LongValue, LongValueEvent};
+        use gatt::{evt, AttOpcode};
+        use message::{MessageClass, MessagePayload};
+
+        let completed = event(
+            MessageClass::gatt,
+            0x06,
+            MessagePayload::evt_gatt_procedure_completed(evt::procedure_completed {
+                connection: 1,
+                result: Error::success,
+            }),
+        );
+
+        let mut long_value = LongValue::new(1);
+        match long_value.reliable_write(0x0010, vec![0x01, 0x02, 0x03]) {
+            Some(LongValueEvent::Command(x)) => assert_eq!(x.header.message_id, 0x13),
+            x => panic!("Unexpected long value event {:?}", x),
+        }
+        long_value.handle(&event(
+            MessageClass::gatt,
+            0x04,
+            MessagePayload::evt_gatt_characteristic_value(evt::characteristic_value {
+                connection: 1,
+                characteristic: 0x0010,
+                att_opcode: AttOpcode::prepare_write_response,
+                offset: 0,
+                value: vec![0x01, 0x02, 0x04],
+            }),
+        ));
+        match long_value.handle(&completed) {
+            Some(LongValueEvent::Command(x)) => assert_eq!(x.header.message_id, 0x0c),
+            x => panic!("Unexpected long value event {:?}", x),
+        }
+        assert_eq!(
+            long_value.handle(&completed),
+            Some(LongValueEvent::Mismatch(Fragment {
+                offset: 0,
+                expected: vec![0x01, 0x02, 0x03],
+                received: vec![0x01, 0x02, 0x04],
+            }))
+        );
+    }
+
+    #[test]
+    fn gatt_subscriptions_confirm_indications_and_resubscribe_bonded_peers() {
+        use error::Error;
+        use gatt::subscription::SubscriptionManager;
+        use gatt::{evt, AttOpcode, ClientConfigFlag};
+        use le_connection::{self, Role};
+        use le_gap::AddressType;
+        use message::{MessageClass, MessagePayload};
+
+        let opened = |connection: u8| {
+            event(
+                MessageClass::le_connection,
+                0x00,
+                MessagePayload::evt_le_connection_opened(le_connection::evt::opened {
+                    address: [0x01, 0x02, 0x03, 0x04, 0x05, 0x06],
+                    address_type: AddressType::public,
+                    master: Role::master,
+                    connection,
+                    bonding: 0,
+                    advertiser: 0xff,
+                }),
+            )
+        };
+
+        let mut subscriptions = SubscriptionManager::new();
+        assert!(subscriptions.handle(&opened(1)).is_empty());
+        let (receiver, command) = subscriptions
+            .subscribe(1, 0x0010, ClientConfigFlag::indication)
+            .unwrap();
+        assert_eq!(command.header.message_id, 0x05);
+
+        let commands = subscriptions.handle(&event(
+            MessageClass::gatt,
+            0x04,
+            MessagePayload::evt_gatt_characteristic_value(evt::characteristic_value {
+                connection: 1,
+                characteristic: 0x0010,
+                att_opcode: AttOpcode::handle_value_indication,
+                offset: 0,
+                value: vec![0x2a],
+            }),
+        ));
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].header.message_id, 0x0d);
+        let notification = receiver.try_recv().unwrap();
+        assert!(notification.indication);
+        assert_eq!(notification.value, vec![0x2a]);
+
+        subscriptions.handle(&event(
+            MessageClass::le_connection,
+            0x01,
+            MessagePayload::evt_le_connection_closed(le_connection::evt::closed {
+                reason: Error::success,
+                connection: 1,
+            }),
+        ));
+        let commands = subscriptions.handle(&opened(2));
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].header.message_id, 0x05);
+        assert!(subscriptions.is_subscribed(2, 0x0010));
+    }
+
+    #[test]
+    fn gatt_descriptor_values_decode_by_uuid() {
+        use gatt::descriptor::{
+            decode, ClientConfiguration, DescriptorValue, Format, CLIENT_CONFIGURATION,
+            PRESENTATION_FORMAT, VALID_RANGE,
+        };
+        use gatt::evt;
+        use gatt::CharacteristicProperties;
+
+        let data = [0x01, 0x03, 0x00, 0x18, 0x02, 0x19, 0x2a];
+        let characteristic = evt::characteristic::from(&data[..]);
+        assert!(characteristic
+            .properties
+            .contains(CharacteristicProperties::WRITE | CharacteristicProperties::NOTIFY));
+
+        assert_eq!(
+            decode(&CLIENT_CONFIGURATION, &[0x02, 0x00]),
+            DescriptorValue::ClientConfiguration(ClientConfiguration {
+                notification: false,
+                indication: true,
+            })
+        );
+        match decode(
+            &PRESENTATION_FORMAT,
+            &[0x0e, 0xfe, 0x2f, 0x27, 0x01, 0x00, 0x00],
+        ) {
+            DescriptorValue::PresentationFormat(x) => {
+                assert_eq!(x.format, Format::sint16);
+                assert_eq!(x.unit, 0x272f);
+                assert!((x.scale(2150) - 21.5).abs() < 1e-9);
+            }
+            x => panic!("Unexpected descriptor value {:?}", x),
+        }
+        match decode(&VALID_RANGE, &[0xf6, 0xff, 0x64, 0x00]) {
+            DescriptorValue::ValidRange(x) => assert_eq!(x.bounds(true), Some((-10, 100))),
+            x => panic!("Unexpected descriptor value {:?}", x),
+        }
+        assert_eq!(
+            decode(&CLIENT_CONFIGURATION, &[0x01]),
+            DescriptorValue::Unknown(vec![0x01])
+        );
+    }
+
+    #[test]
+    fn gatt_codec_decodes_and_encodes_standard_characteristics() {
+        use gatt::codec::{
+            decode, CharacteristicValue, Float, HeartRateMeasurement, BATTERY_LEVEL,
+            HEART_RATE_MEASUREMENT, TEMPERATURE_MEASUREMENT,
+        };
+        use gatt_server::cmd::write_attribute_value;
+        use message::MessagePayload;
+
+        let data = [0x16, 0x48, 0x2c, 0x01, 0x00, 0x04];
+        let value = decode(&HEART_RATE_MEASUREMENT, &data).unwrap();
+        assert_eq!(
+            value,
+            CharacteristicValue::HeartRateMeasurement(HeartRateMeasurement {
+                heart_rate: 72,
+                sensor_contact: Some(true),
+                energy_expended: None,
+                rr_intervals: vec![0x012c, 0x0400],
+            })
+        );
+        assert_eq!(value.encode(), data.to_vec());
+
+        let data = [0x00, 0x6b, 0x0e, 0x00, 0xfe];
+        match decode(&TEMPERATURE_MEASUREMENT, &data) {
+            Some(CharacteristicValue::TemperatureMeasurement(x)) => {
+                assert!((x.temperature.to_f64() - 36.91).abs() < 1e-9);
+                assert!(!x.fahrenheit);
+            }
+            x => panic!("Unexpected characteristic value {:?}", x),
+        }
+        assert_eq!(Float::from_sfloat(0xf172).to_f64(), 37.0);
+        assert_eq!(Float::from_f64(37.0, -1).to_sfloat(), 0xf172);
+        assert!(Float::from_sfloat(0x07ff).to_f64().is_nan());
+
+        // Out of range values lose digits or become infinite.
+        assert_eq!(Float::from_f64(36.912, -3).to_sfloat(), 0xf171);
+        assert_eq!(Float::from_f64(15e-9, -9).to_sfloat(), 0x8002);
+        assert_eq!(Float::from_f64(5e12, 0).to_sfloat(), 0x07fe);
+        assert_eq!(Float::from_f64(-5e12, 0).to_sfloat(), 0x0802);
+        assert_eq!(Float::from_f64(1e300, 0), Float::POSITIVE_INFINITY);
+        assert_eq!(Float::from_f64(12_345_678.0, 0).to_float(), 0x0112_d688);
+
+        let message =
+            write_attribute_value::new(0x0003, 0, CharacteristicValue::BatteryLevel(87).encode());
+        match message.payload {
+            MessagePayload::cmd_gatt_server_write_attribute_value(x) => {
+                assert_eq!(
+                    decode(&BATTERY_LEVEL, &x.value),
+                    Some(CharacteristicValue::BatteryLevel(87))
+                );
+            }
+            x => panic!("Unexpected payload {:?}", x),
+        }
+    }
+
+    #[test]
+    fn gatt_batch_read_splits_multiple_read_response() {
+        use error::Error;
+        use gatt::batch_read::{BatchRead, BatchReadEvent};
+        use gatt::{evt, AttOpcode};
+        use message::{MessageClass, MessagePayload};
+
+        let mut batch_read = BatchRead::new(1);
+        match batch_read.read_multiple(vec![(0x0003, 1), (0x0005, 2), (0x0007, 0)]) {
+            Some(BatchReadEvent::Command(x)) => assert_eq!(x.header.message_id, 0x11),
+            x => panic!("Unexpected batch read event {:?}", x),
+        }
+        batch_read.handle(&event(
+            MessageClass::gatt,
+            0x04,
+            MessagePayload::evt_gatt_characteristic_value(evt::characteristic_value {
+                connection: 1,
+                characteristic: 0x0003,
+                att_opcode: AttOpcode::read_multiple_response,
+                offset: 0,
+                value: vec![0x57, 0x40, 0x06, b'a', b'b', b'c'],
+            }),
+        ));
+        assert_eq!(
+            batch_read.handle(&event(
+                MessageClass::gatt,
+                0x06,
+                MessagePayload::evt_gatt_procedure_completed(evt::procedure_completed {
+                    connection: 1,
+                    result: Error::success,
+                },)
+            )),
+            Some(BatchReadEvent::Values(vec![
+                (0x0003, vec![0x57]),
+                (0x0005, vec![0x40, 0x06]),
+                (0x0007, b"abc".to_vec()),
+            ]))
+        );
+    }
+
+    #[test]
+    fn gatt_server_handler_registry_answers_user_requests() {
+        use error::Error;
+        use gatt_server::handler::{read_at, AttributeHandler, HandlerRegistry};
+        use gatt_server::{evt, AttErrorCode};
+        use message::{MessageClass, MessagePayload};
+
+        struct Name(Vec<u8>);
+
+        impl AttributeHandler for Name {
+            fn read(&mut self, _connection: u8, offset: u16) -> Result<Vec<u8>, AttErrorCode> {
+                read_at(&self.0, offset)
+            }
+
+            fn write(
+                &mut self,
+                _connection: u8,
+                offset: u16,
+                value: &[u8],
+            ) -> Result<(), AttErrorCode> {
+                if offset as usize > self.0.len() {
+                    return Err(AttErrorCode::invalid_offset);
+                }
+                self.0.truncate(offset as usize);
+                self.0.extend_from_slice(value);
+                Ok(())
+            }
+        }
+
+        let write = |att_opcode: u8, offset: u16, value: &[u8]| {
+            event(
+                MessageClass::gatt_server,
+                0x02,
+                MessagePayload::evt_gatt_server_user_write_request(evt::user_write_request {
+                    connection: 1,
+                    characteristic: 0x0010,
+                    att_opcode,
+                    offset,
+                    value: value.to_vec(),
+                }),
+            )
+        };
+        let read = |characteristic: u16, offset: u16| {
+            event(
+                MessageClass::gatt_server,
+                0x01,
+                MessagePayload::evt_gatt_server_user_read_request(evt::user_read_request {
+                    connection: 1,
+                    characteristic,
+                    att_opcode: 0x0c,
+                    offset,
+                }),
+            )
+        };
+
+        let mut registry = HandlerRegistry::new();
+        registry.register(0x0010, Box::new(Name(b"bgapi".to_vec())));
+
+        match registry.handle(&read(0x0010, 2)).unwrap().payload {
+            MessagePayload::cmd_gatt_server_send_user_read_response(x) => {
+                assert_eq!(x.att_errorcode, AttErrorCode::success as u8);
+                assert_eq!(x.value, b"api".to_vec());
+            }
+            x => panic!("Unexpected payload {:?}", x),
+        }
+        match registry.handle(&read(0x0020, 0)).unwrap().payload {
+            MessagePayload::cmd_gatt_server_send_user_read_response(x) => {
+                assert_eq!(x.att_errorcode, AttErrorCode::read_not_permitted as u8);
+            }
+            x => panic!("Unexpected payload {:?}", x),
+        }
+
+        registry.handle(&write(0x16, 0, b"rust"));
+        registry.handle(&write(0x16, 4, b"-ble"));
+        match registry.handle(&write(0x18, 0, &[0x01])).unwrap().payload {
+            MessagePayload::cmd_gatt_server_send_user_write_response(x) => {
+                assert_eq!(x.att_errorcode, AttErrorCode::success as u8);
+            }
+            x => panic!("Unexpected payload {:?}", x),
+        }
+        match registry.handle(&read(0x0010, 0)).unwrap().payload {
+            MessagePayload::cmd_gatt_server_send_user_read_response(x) => {
+                assert_eq!(x.value, b"rust-ble".to_vec());
+            }
+            x => panic!("Unexpected payload {:?}", x),
+        }
+
+        // The first failed fragment is reported in the execute write response.
+        registry.handle(&write(0x16, 0, b"ok"));
+        registry.handle(&write(0x16, 40, b"x"));
+        match registry.handle(&write(0x18, 0, &[0x01])).unwrap().payload {
+            MessagePayload::cmd_gatt_server_send_user_write_response(x) => {
+                assert_eq!(x.att_errorcode, AttErrorCode::invalid_offset as u8);
+            }
+            x => panic!("Unexpected payload {:?}", x),
+        }
+
+        // Writes executed by the stack on its own are still applied.
+        registry.handle(&write(0x16, 0, b"rust"));
+        assert_eq!(
+            registry.handle(&event(
+                MessageClass::gatt_server,
+                0x04,
+                MessagePayload::evt_gatt_server_execute_write_completed(
+                    evt::execute_write_completed {
+                        connection: 1,
+                        result: Error::success,
+                    },
+                ),
+            )),
+            None
+        );
+        match registry.handle(&read(0x0010, 0)).unwrap().payload {
+            MessagePayload::cmd_gatt_server_send_user_read_response(x) => {
+                assert_eq!(x.value, b"rust".to_vec());
+            }
+            x => panic!("Unexpected payload {:?}", x),
+        }
+        match registry.handle(&write(0x12, 20, b"x")).unwrap().payload {
+            MessagePayload::cmd_gatt_server_send_user_write_response(x) => {
+                assert_eq!(x.att_errorcode, AttErrorCode::invalid_offset as u8);
+            }
+            x => panic!("Unexpected payload {:?}", x),
+        }
+        assert_eq!(registry.handle(&write(0x52, 0, b"x")), None);
+    }
+
+    #[test]
+    fn gatt_server_notifier_sends_to_subscribers_and_queues_indications() {
+        use gatt_server::evt;
+        use gatt_server::notifier::Notifier;
+        use message::{Message, MessageClass, MessagePayload};
+
+        let status = |connection: u8, status_flags: u8, client_config_flags: u16| {
+            event(
+                MessageClass::gatt_server,
+                0x03,
+                MessagePayload::evt_gatt_server_characteristic_status(evt::characteristic_status {
+                    connection,
+                    characteristic: 0x0010,
+                    status_flags,
+                    client_config_flags,
+                }),
+            )
+        };
+
+        let mut notifier = Notifier::new();
+        notifier.handle(&status(1, 0x01, 0x0001));
+        notifier.handle(&status(2, 0x01, 0x0002));
+        assert_eq!(notifier.subscribers(0x0010), vec![1, 2]);
+
+        let commands = notifier.notify_all(0x0010, &[0x01]);
+        assert_eq!(commands.len(), 2);
+        let commands = notifier.notify_all(0x0010, &[0x02]);
+        assert_eq!(commands.len(), 1);
+        match commands[0].payload {
+            MessagePayload::cmd_gatt_server_send_characteristic_notification(ref x) => {
+                assert_eq!(x.connection, 1)
+            }
+            ref x => panic!("Unexpected payload {:?}", x),
+        }
+        assert_eq!(notifier.queued(2), 1);
+
+        match notifier.handle(&status(2, 0x02, 0x0000)) {
+            Some(Message {
+                payload: MessagePayload::cmd_gatt_server_send_characteristic_notification(x),
+                ..
+            }) => {
+                assert_eq!(x.connection, 2);
+                assert_eq!(x.value, vec![0x02]);
+            }
+            x => panic!("Unexpected message {:?}", x),
+        }
+        assert_eq!(notifier.queued(2), 0);
+    }
+
+    #[test]
+    fn gatt_server_dump_rebuilds_local_database() {
+        use error::Error;
+        use gatt::CharacteristicProperties;
+        use gatt_server::dump::{Dump, DumpEvent};
+        use gatt_server::rsp;
+        use message::{Message, MessageClass, MessagePayload};
+
+        let attributes: Vec<(Vec<u8>, Vec<u8>)> = vec![
+            (vec![0x00, 0x28], vec![0x0f, 0x18]),
+            (vec![0x03, 0x28], vec![0x12, 0x03, 0x00, 0x19, 0x2a]),
+            (vec![0x19, 0x2a], vec![0x64]),
+            (vec![0x02, 0x29], vec![0x00, 0x00]),
+        ];
+        let respond = |command: &Message| {
+            let payload = match command.payload {
+                MessagePayload::cmd_gatt_server_find_attribute(_) => {
+                    MessagePayload::rsp_gatt_server_find_attribute(rsp::find_attribute {
+                        result: Error::success,
+                        attribute: 1,
+                    })
+                }
+                MessagePayload::cmd_gatt_server_read_attribute_type(ref x) => {
+                    MessagePayload::rsp_gatt_server_read_attribute_type(
+                        match attributes.get(x.attribute as usize - 1) {
+                            Some(attribute) => rsp::read_attribute_type {
+                                result: Error::success,
+                                atype: attribute.0.clone(),
+                            },
+                            None => rsp::read_attribute_type {
+                                result: Error::invalid_handle,
+                                atype: Vec::new(),
+                            },
+                        },
+                    )
+                }
+                MessagePayload::cmd_gatt_server_read_attribute_value(ref x) => {
+                    let value = &attributes[x.attribute as usize - 1].1;
+                    MessagePayload::rsp_gatt_server_read_attribute_value(
+                        rsp::read_attribute_value {
+                            result: Error::success,
+                            value: value[x.offset as usize..].to_vec(),
+                        },
+                    )
+                }
+                ref x => panic!("Unexpected command {:?}", x),
+            };
+            response(
+                MessageClass::gatt_server,
+                command.header.message_id,
+                payload,
+            )
+        };
+
+        let mut dump = Dump::new();
+        let mut event = dump.start();
+        let database = loop {
+            event = match event {
+                DumpEvent::Command(ref x) => dump.handle(&respond(x)).unwrap(),
+                DumpEvent::Complete(x) => break x,
+                DumpEvent::Failed(x) => panic!("Dump failed with {:?}", x),
+            };
+        };
+
+        assert_eq!(database.services.len(), 1);
+        assert_eq!(database.services[0].uuid, vec![0x18, 0x0f]);
+        let characteristic = &database.services[0].characteristics[0];
+        assert_eq!(characteristic.value_handle, 3);
+        assert!(characteristic
+            .properties
+            .contains(CharacteristicProperties::NOTIFY));
+        assert_eq!(characteristic.value, vec![0x64]);
+        assert_eq!(characteristic.descriptors[0].uuid, vec![0x29, 0x02]);
+        assert!(database
+            .to_json()
+            .contains("\"uuid\":\"2a19\",\"properties\":[\"read\", \"notify\"]"));
+        let xml = database.to_gatt_xml();
+        assert!(xml.contains("<properties read=\"true\" notify=\"true\"/>"));
+        assert!(!xml.contains("2902"));
+    }
+
+    #[test]
+    fn gatt_server_declaration_assigns_handles_and_emits_gatt_xml() {
+        use gatt::CharacteristicProperties;
+        use gatt_server::declaration::{
+            CharacteristicSpec, DatabaseSpec, DescriptorSpec, ServiceSpec, Value,
+        };
+
+        let database = DatabaseSpec::new()
+            .service(
+                ServiceSpec::new("generic_access", &[0x18, 0x00]).characteristic(
+                    CharacteristicSpec::new(
+                        "device_name",
+                        &[0x2a, 0x00],
+                        CharacteristicProperties::READ,
+                        Value::Utf8(String::from("Thermo & Co")),
+                    ),
+                ),
+            )
+            .service(
+                ServiceSpec::new("battery", &[0x18, 0x0f]).characteristic(
+                    CharacteristicSpec::new(
+                        "battery_level",
+                        &[0x2a, 0x19],
+                        CharacteristicProperties::READ | CharacteristicProperties::NOTIFY,
+                        Value::User(1),
+                    )
+                    .descriptor(DescriptorSpec::new(
+                        &[0x29, 0x01],
+                        Value::Constant(b"Level".to_vec()),
+                    )),
+                ),
+            );
+
+        let handles = database.handles();
+        assert_eq!(handles.handle("device_name"), Some(3));
+        assert_eq!(handles.handle("battery"), Some(4));
+        assert_eq!(handles.handle("battery_level"), Some(6));
+        assert_eq!(handles.id(6), Some("battery_level"));
+        assert!(handles.is_user(6));
+        assert_eq!(
+            handles
+                .write("device_name", b"x".to_vec())
+                .unwrap()
+                .header
+                .message_id,
+            0x02
+        );
+
+        let xml = database.to_gatt_xml();
+        assert!(xml.contains("<characteristic uuid=\"2a19\" id=\"battery_level\">"));
+        assert!(xml.contains("<value type=\"utf-8\" length=\"11\">Thermo &amp; Co</value>"));
+        assert!(xml.contains("<value type=\"user\" length=\"1\"/>"));
+        assert!(xml.contains("<properties read=\"true\" const=\"true\"/>"));
+
+        // The same database as it would be dumped from the stack.
+        let built = database.to_database();
+        let descriptors = &built.services[1].characteristics[0].descriptors;
+        assert_eq!(descriptors[0].handle, 7);
+        assert_eq!(descriptors[0].uuid, vec![0x29, 0x02]);
+        assert_eq!(descriptors[1].handle, 8);
+        assert_eq!(built.to_gatt_xml(), xml);
+    }
+
+    #[test]
+    fn le_connection_manager_folds_connection_events() {
+        use error::Error;
+        use gatt;
+        use le_connection::manager::{ConnectionEvent, ConnectionManager};
+        use le_connection::{evt, Role, Security};
+        use le_gap::AddressType;
+        use message::{MessageClass, MessagePayload};
+        use sm;
+
+        let address = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+
+        let mut manager = ConnectionManager::new();
+        assert_eq!(manager.close(1), None);
+        match manager.handle(&event(
+            MessageClass::le_connection,
+            0x00,
+            MessagePayload::evt_le_connection_opened(evt::opened {
+                address,
+                address_type: AddressType::public,
+                master: Role::master,
+                connection: 1,
+                bonding: 0xff,
+                advertiser: 0xff,
+            }),
+        )) {
+            Some(ConnectionEvent::Opened(x)) => {
+                assert_eq!(x.role, Role::master);
+                assert_eq!(x.bonding, None);
+            }
+            x => panic!("Unexpected connection event {:?}", x),
+        }
+        manager.handle(&event(
+            MessageClass::le_connection,
+            0x02,
+            MessagePayload::evt_le_connection_parameters(evt::parameters {
+                connection: 1,
+                interval: 40,
+                latency: 0,
+                timeout: 100,
+                security_mode: Security::mode1_level2,
+                txsize: 27,
+            }),
+        ));
+        match manager.handle(&event(
+            MessageClass::gatt,
+            0x00,
+            MessagePayload::evt_gatt_mtu_exchanged(gatt::evt::mtu_exchanged {
+                connection: 1,
+                mtu: 247,
+            }),
+        )) {
+            Some(ConnectionEvent::Updated(x)) => {
+                assert_eq!(x.interval, 40);
+                assert_eq!(x.security_mode, Security::mode1_level2);
+                assert_eq!(x.mtu, 247);
+            }
+            x => panic!("Unexpected connection event {:?}", x),
+        }
+        assert_eq!(manager.find(&address).unwrap().connection, 1);
+        assert_eq!(manager.set_phy(1, 0x02).unwrap().header.message_id, 0x03);
+        match manager.handle(&event(
+            MessageClass::sm,
+            0x03,
+            MessagePayload::evt_sm_bonded(sm::evt::bonded {
+                connection: 1,
+                bonding: 2,
+            }),
+        )) {
+            Some(ConnectionEvent::Updated(x)) => assert_eq!(x.bonding, Some(2)),
+            x => panic!("Unexpected connection event {:?}", x),
+        }
+
+        match manager.handle(&event(
+            MessageClass::le_connection,
+            0x01,
+            MessagePayload::evt_le_connection_closed(evt::closed {
+                reason: Error::connection_timeout,
+                connection: 1,
+            }),
+        )) {
+            Some(ConnectionEvent::Closed(x, Error::connection_timeout)) => {
+                assert_eq!(x.address, address)
+            }
+            x => panic!("Unexpected connection event {:?}", x),
+        }
+        assert!(manager.is_empty());
+    }
+
+    #[test]
+    fn le_gap_connector_times_out_and_configures_connection() {
+        use error::Error;
+        use le_connection::ConnectionParameters;
+        use le_connection::{evt, Role};
+        use le_gap::connector::{ConnectConfig, ConnectEvent, Connector};
+        use le_gap::{rsp, AddressType};
+        use message::{MessageClass, MessagePayload};
+        use std::time::{Duration, Instant};
+
+        let opened = |connection: u8| {
+            event(
+                MessageClass::le_connection,
+                0x00,
+                MessagePayload::evt_le_connection_opened(evt::opened {
+                    address: [0x01, 0x02, 0x03, 0x04, 0x05, 0x06],
+                    address_type: AddressType::public,
+                    master: Role::master,
+                    connection,
+                    bonding: 0xff,
+                    advertiser: 0xff,
+                }),
+            )
+        };
+        let connected = |connection: u8| {
+            response(
+                MessageClass::le_gap,
+                0x1a,
+                MessagePayload::rsp_le_gap_connect(rsp::connect {
+                    result: Error::success,
+                    connection,
+                }),
+            )
+        };
+        let address = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+        let now = Instant::now();
+
+        let mut connector = Connector::new(ConnectConfig {
+            timeout: Duration::from_secs(2),
+            parameters: Some(ConnectionParameters {
+                min_interval: 6,
+                max_interval: 12,
+                latency: 0,
+                timeout: 100,
+            }),
+            preferred_parameters: Some(ConnectionParameters {
+                min_interval: 40,
+                max_interval: 80,
+                latency: 4,
+                timeout: 400,
+            }),
+            phy_2m: true,
+            max_mtu: Some(247),
+            ..ConnectConfig::default()
+        });
+        let commands = connector.connect(address, AddressType::public, now);
+        let ids: Vec<(MessageClass, u8)> = commands
+            .iter()
+            .map(|x| (x.header.message_class.clone(), x.header.message_id))
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                (MessageClass::gatt, 0x00),
+                (MessageClass::le_gap, 0x05),
+                (MessageClass::le_gap, 0x1a),
+            ]
+        );
+        assert!(connector.handle(&connected(1)).is_empty());
+        assert!(connector.poll(now + Duration::from_secs(1)).is_empty());
+        match connector.poll(now + Duration::from_secs(2)).as_slice() {
+            [ConnectEvent::Command(x), ConnectEvent::TimedOut] => {
+                assert_eq!(x.header.message_id, 0x03)
+            }
+            x => panic!("Unexpected connect events {:?}", x),
+        }
+        assert!(!connector.is_connecting());
+        // Opened while the procedure was being ended.
+        match connector.handle(&opened(1)).as_slice() {
+            [ConnectEvent::Command(x)] => assert_eq!(x.header.message_id, 0x04),
+            x => panic!("Unexpected connect events {:?}", x),
+        }
+
+        connector.connect(address, AddressType::public, now);
+        assert!(connector.handle(&connected(2)).is_empty());
+        match connector.handle(&opened(2)).as_slice() {
+            [ConnectEvent::Command(x), ConnectEvent::Command(y), ConnectEvent::Connected(2)] => {
+                assert_eq!(x.header.message_id, 0x00);
+                assert_eq!(y.header.message_id, 0x03);
+            }
+            x => panic!("Unexpected connect events {:?}", x),
+        }
+        assert_eq!(connector.connection(), Some(2));
+
+        connector.connect(address, AddressType::public, now);
+        assert!(connector.handle(&connected(3)).is_empty());
+        assert_eq!(
+            connector.handle(&event(
+                MessageClass::le_connection,
+                0x01,
+                MessagePayload::evt_le_connection_closed(evt::closed {
+                    reason: Error::connection_timeout,
+                    connection: 3,
+                })
+            )),
+            vec![ConnectEvent::Failed(Error::connection_timeout)]
+        );
+
+        // Timed out before the stack answered the connect command.
+        connector.connect(address, AddressType::public, now);
+        assert_eq!(connector.poll(now + Duration::from_secs(2)).len(), 2);
+        connector.connect(address, AddressType::public, now);
+        assert!(connector.handle(&connected(4)).is_empty());
+        assert!(connector.handle(&connected(5)).is_empty());
+        match connector.handle(&opened(4)).as_slice() {
+            [ConnectEvent::Command(x)] => assert_eq!(x.header.message_id, 0x04),
+            x => panic!("Unexpected connect events {:?}", x),
+        }
+        match connector.handle(&opened(5)).as_slice() {
+            [.., ConnectEvent::Connected(5)] => (),
+            x => panic!("Unexpected connect events {:?}", x),
+        }
+    }
+
+    #[test]
+    fn le_connection_opened_and_channel_map_from_bytes() {
+        use error::Error;
+        use le_connection::{evt, rsp, Role};
+        use le_gap::AddressType;
+        use message::MessagePayload;
+        use parser::parse_next_message;
+
+        let (tx, rx) = spmc::channel();
+        let bytes = [
+            0xa0, 0x0b, 0x08, 0x00, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01, 0x01, 0x00, 0x02, 0xff,
+            0x00, 0x20, 0x08, 0x08, 0x06, 0x00, 0x00, 0x05, 0xff, 0xff, 0xff, 0xff, 0x1f,
+        ];
+        for x in &bytes {
+            tx.send(*x).unwrap();
+        }
+        let opened = parse_next_message(&rx).expect("Failed parsing message.");
+        assert_eq!(
+            opened.payload,
+            MessagePayload::evt_le_connection_opened(evt::opened {
+                address: [0x01, 0x02, 0x03, 0x04, 0x05, 0x06],
+                address_type: AddressType::random,
+                master: Role::slave,
+                connection: 2,
+                bonding: 0xff,
+                advertiser: 0,
+            })
+        );
+        let channel_map = parse_next_message(&rx).expect("Failed parsing message.");
+        assert_eq!(
+            channel_map.payload,
+            MessagePayload::rsp_le_connection_read_channel_map(rsp::read_channel_map {
+                result: Error::success,
+                channel_map: vec![0xff, 0xff, 0xff, 0xff, 0x1f],
+            })
+        );
+        let opened: Vec<u8> = opened.payload.into();
+        assert_eq!(opened, bytes[4..15].to_vec());
+    }
+
+    #[test]
+    fn le_connection_rssi_monitor_polls_and_reports_proximity() {
+        use error::Error;
+        use le_connection::rssi::{Proximity, RssiConfig, RssiEvent, RssiMonitor};
+        use le_connection::{evt, Role};
+        use le_gap::{self, AddressType, PacketType};
+        use message::{MessageClass, MessagePayload};
+        use std::time::{Duration, Instant};
+
+        let rssi = |rssi: i8| {
+            event(
+                MessageClass::le_connection,
+                0x03,
+                MessagePayload::evt_le_connection_rssi(evt::rssi {
+                    connection: 1,
+                    status: 0,
+                    rssi,
+                }),
+            )
+        };
+        let peer = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+        let beacon = [0x11, 0x12, 0x13, 0x14, 0x15, 0x16];
+        let now = Instant::now();
+
+        let mut monitor = RssiMonitor::new(RssiConfig {
+            window: 4,
+            ..RssiConfig::default()
+        });
+        monitor.handle(&event(
+            MessageClass::le_connection,
+            0x00,
+            MessagePayload::evt_le_connection_opened(evt::opened {
+                address: peer,
+                address_type: AddressType::public,
+                master: Role::master,
+                connection: 1,
+                bonding: 0xff,
+                advertiser: 0xff,
+            }),
+        ));
+        assert_eq!(monitor.poll(now).len(), 1);
+        assert!(monitor.poll(now + Duration::from_millis(500)).is_empty());
+        assert_eq!(monitor.poll(now + Duration::from_secs(1)).len(), 1);
+
+        assert_eq!(
+            monitor.handle(&rssi(-80)),
+            Some(RssiEvent::Proximity(peer, Proximity::Far))
+        );
+        let mut events = Vec::new();
+        for x in &[-70, -50, -52, -54] {
+            events.extend(monitor.handle(&rssi(*x)));
+        }
+        let stats = monitor.stats(&peer).unwrap();
+        assert_eq!(stats.len(), 4);
+        assert_eq!(stats.mean(), Some(-56.5));
+        assert_eq!(stats.median(), Some(-53.0));
+        for _ in 0..20 {
+            events.extend(monitor.handle(&rssi(-45)));
+        }
+        assert_eq!(events, vec![RssiEvent::Proximity(peer, Proximity::Near)]);
+
+        // Unconnected devices are sampled from scan reports. TX power level
+        // 0 dBm at -41 dBm is 1 m away.
+        monitor.handle(&event(
+            MessageClass::le_gap,
+            0x00,
+            MessagePayload::evt_le_gap_scan_response(le_gap::evt::scan_response {
+                rssi: -41,
+                packet_type: PacketType::from(0),
+                address: beacon,
+                address_type: AddressType::random,
+                bonding: 0xff,
+                data: vec![0x02, 0x0a, 0x00],
+            }),
+        ));
+        assert!((monitor.distance(&beacon).unwrap() - 1.0).abs() < 1e-9);
+        assert_eq!(monitor.distance(&peer), None);
+
+        // The filtered RSSI only covers the window.
+        for _ in 0..4 {
+            monitor.handle(&rssi(-90));
+        }
+        assert_eq!(monitor.stats(&peer).unwrap().kalman(), Some(-90.0));
+
+        // Once disconnected the peer starts over from its scan reports.
+        for _ in 0..4 {
+            monitor.handle(&rssi(-45));
+        }
+        assert_eq!(
+            monitor.stats(&peer).unwrap().proximity,
+            Some(Proximity::Near)
+        );
+        monitor.handle(&event(
+            MessageClass::le_connection,
+            0x01,
+            MessagePayload::evt_le_connection_closed(evt::closed {
+                reason: Error::connection_timeout,
+                connection: 1,
+            }),
+        ));
+        assert!(monitor.stats(&peer).unwrap().is_empty());
+        assert_eq!(
+            monitor.handle(&event(
+                MessageClass::le_gap,
+                0x00,
+                MessagePayload::evt_le_gap_scan_response(le_gap::evt::scan_response {
+                    rssi: -85,
+                    packet_type: PacketType::from(0),
+                    address: peer,
+                    address_type: AddressType::public,
+                    bonding: 0xff,
+                    data: Vec::new(),
+                }),
+            )),
+            Some(RssiEvent::Proximity(peer, Proximity::Far))
+        );
+    }
+
+    #[test]
+    fn sm_pairing_manager_asks_user_interface() {
+        use error::Error;
+        use le_connection::{self, Security};
+        use message::{MessageClass, MessagePayload};
+        use sm::pairing::{PairingConfig, PairingEvent, PairingManager, PairingState, PairingUi};
+        use sm::{evt, rsp};
+        use std::cell::RefCell;
+        use std::rc::Rc;
+        use std::time::{Duration, Instant};
+
+        struct Ui(Rc<RefCell<Vec<u32>>>);
+
+        impl PairingUi for Ui {
+            fn display_passkey(&mut self, _connection: u8, passkey: u32) {
+                self.0.borrow_mut().push(passkey);
+            }
+
+            fn confirm_passkey(&mut self, _connection: u8, passkey: u32) -> Option<bool> {
+                Some(passkey == 123456)
+            }
+        }
+
+        let displayed = Rc::new(RefCell::new(Vec::new()));
+        let now = Instant::now();
+        let increase_security = |result: Error| {
+            response(
+                MessageClass::sm,
+                0x04,
+                MessagePayload::rsp_sm_increase_security(rsp::increase_security { result }),
+            )
+        };
+
+        let mut manager = PairingManager::new(
+            PairingConfig::default(),
+            Box::new(Ui(Rc::clone(&displayed))),
+        );
+        assert_eq!(manager.setup().len(), 2);
+        assert_eq!(manager.pair(1, now).header.message_id, 0x04);
+        assert_eq!(
+            manager.handle(&increase_security(Error::success), now),
+            None
+        );
+        manager.handle(
+            &event(
+                MessageClass::sm,
+                0x00,
+                MessagePayload::evt_sm_passkey_display(evt::passkey_display {
+                    connection: 1,
+                    passkey: 123456,
+                }),
+            ),
+            now,
+        );
+        assert_eq!(*displayed.borrow(), vec![123456]);
+        match manager.handle(
+            &event(
+                MessageClass::sm,
+                0x02,
+                MessagePayload::evt_sm_confirm_passkey(evt::confirm_passkey {
+                    connection: 1,
+                    passkey: 123456,
+                }),
+            ),
+            now,
+        ) {
+            Some(PairingEvent::Command(x)) => {
+                assert_eq!(x.header.message_id, 0x09);
+                let payload: Vec<u8> = x.payload.into();
+                assert_eq!(payload, vec![0x01, 0x01]);
+            }
+            x => panic!("Unexpected pairing event {:?}", x),
+        }
+        manager.handle(
+            &event(
+                MessageClass::le_connection,
+                0x02,
+                MessagePayload::evt_le_connection_parameters(le_connection::evt::parameters {
+                    connection: 1,
+                    interval: 40,
+                    latency: 0,
+                    timeout: 100,
+                    security_mode: Security::mode1_level4,
+                    txsize: 27,
+                }),
+            ),
+            now,
+        );
+        assert_eq!(
+            manager.handle(
+                &event(
+                    MessageClass::sm,
+                    0x03,
+                    MessagePayload::evt_sm_bonded(evt::bonded {
+                        connection: 1,
+                        bonding: 0,
+                    })
+                ),
+                now,
+            ),
+            Some(PairingEvent::Paired {
+                connection: 1,
+                bonding: Some(0),
+                security: Security::mode1_level4,
+            })
+        );
+
+        // Passkey entry is answered later and times out if it never is.
+        manager.handle(
+            &event(
+                MessageClass::sm,
+                0x01,
+                MessagePayload::evt_sm_passkey_request(evt::passkey_request { connection: 2 }),
+            ),
+            now,
+        );
+        assert_eq!(manager.state(2), Some(PairingState::AwaitingPasskey));
+        assert_eq!(manager.confirm_bonding(2, true), None);
+        assert!(manager.poll(now + Duration::from_secs(29)).is_empty());
+        assert_eq!(
+            manager.poll(now + Duration::from_secs(30)),
+            vec![PairingEvent::TimedOut(2)]
+        );
+        assert_eq!(manager.enter_passkey(2, 0), None);
+        let bonded = |connection: u8| {
+            event(
+                MessageClass::sm,
+                0x03,
+                MessagePayload::evt_sm_bonded(evt::bonded {
+                    connection,
+                    bonding: 1,
+                }),
+            )
+        };
+        assert_eq!(manager.handle(&bonded(2), now), None);
+        assert!(manager.handle(&bonded(2), now).is_some());
+
+        // Responses to increase_security are matched in order.
+        manager.pair(4, now);
+        manager.pair(5, now);
+        assert_eq!(
+            manager.handle(&increase_security(Error::success), now),
+            None
+        );
+        assert_eq!(
+            manager.handle(&increase_security(Error::invalid_param), now),
+            Some(PairingEvent::Failed(5, Error::invalid_param))
+        );
+        assert_eq!(manager.state(4), Some(PairingState::Pairing));
+
+        // Bonding is confirmed later unless the user interface answers.
+        manager.handle(
+            &event(
+                MessageClass::sm,
+                0x09,
+                MessagePayload::evt_sm_confirm_bonding(evt::confirm_bonding {
+                    connection: 4,
+                    bonding_handle: -1,
+                }),
+            ),
+            now,
+        );
+        assert_eq!(manager.state(4), Some(PairingState::AwaitingBonding));
+
+        manager.handle(
+            &event(
+                MessageClass::sm,
+                0x01,
+                MessagePayload::evt_sm_passkey_request(evt::passkey_request { connection: 3 }),
+            ),
+            now,
+        );
+        assert!(manager.enter_passkey(3, 654321).is_some());
+        assert_eq!(
+            manager.handle(
+                &event(
+                    MessageClass::sm,
+                    0x04,
+                    MessagePayload::evt_sm_bonding_failed(evt::bonding_failed {
+                        connection: 3,
+                        reason: Error::passkey_entry_failed,
+                    })
+                ),
+                now,
+            ),
+            Some(PairingEvent::Failed(3, Error::passkey_entry_failed))
+        );
+        assert_eq!(manager.state(3), None);
+    }
+
+    #[test]
+    fn sm_configure_with_typed_flags() {
+        use message::MessagePayload;
+        use sm::cmd::{configure, store_bonding_configuration};
+        use sm::{io_capability, BondingPolicy, ConfigureFlags};
+
+        let flags = ConfigureFlags::BONDING_REQUIRES_MITM | ConfigureFlags::SECURE_CONNECTIONS_ONLY;
+        let message = configure::new(flags, io_capability::keyboarddisplay);
+        let bytes: Vec<u8> = message.payload.into();
+        assert_eq!(bytes, vec![0x05, 0x04]);
+        match MessagePayload::cmd_sm_configure(configure::from(&bytes[..])) {
+            MessagePayload::cmd_sm_configure(x) => {
+                assert!(x.flags.contains(ConfigureFlags::SECURE_CONNECTIONS_ONLY));
+                assert!(!x.flags.contains(ConfigureFlags::CONFIRM_BONDING_REQUESTS));
+                assert_eq!(x.io_capabilities, io_capability::keyboarddisplay);
+            }
+            x => panic!("Unexpected payload {:?}", x),
+        }
+
+        let message =
+            store_bonding_configuration::new(8, BondingPolicy::overwrite_least_recently_used);
+        let bytes: Vec<u8> = message.payload.into();
+        assert_eq!(bytes, vec![0x08, 0x02]);
+        assert_eq!(
+            store_bonding_configuration::from(&bytes[..]).policy_flags,
+            BondingPolicy::overwrite_least_recently_used
+        );
+    }
+
+    #[test]
+    fn sm_bonding_store_lists_and_keeps_metadata() {
+        use error::Error;
+        use le_connection::{self, Role};
+        use le_gap::AddressType;
+        use message::{MessageClass, MessagePayload};
+        use sm::bonding::{BondingEvent, BondingStore};
+        use sm::{evt, rsp};
+        use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+        let address = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+        let now = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+
+        let mut store = BondingStore::new();
+        assert_eq!(store.list().header.message_id, 0x0b);
+        for message in [
+            response(
+                MessageClass::sm,
+                0x0b,
+                MessagePayload::rsp_sm_list_all_bondings(rsp::list_all_bondings {
+                    result: Error::success,
+                }),
+            ),
+            event(
+                MessageClass::sm,
+                0x05,
+                MessagePayload::evt_sm_list_bonding_entry(evt::list_bonding_entry {
+                    bonding: 3,
+                    address,
+                    address_type: 2,
+                }),
+            ),
+            event(
+                MessageClass::sm,
+                0x05,
+                MessagePayload::evt_sm_list_bonding_entry(evt::list_bonding_entry {
+                    bonding: 4,
+                    address: [0x31, 0x32, 0x33, 0x34, 0x35, 0x36],
+                    address_type: 0x10,
+                }),
+            ),
+        ] {
+            assert_eq!(store.handle(&message, now), None);
+        }
+        match store.handle(
+            &event(
+                MessageClass::sm,
+                0x06,
+                MessagePayload::evt_sm_list_all_bondings_complete(
+                    evt::list_all_bondings_complete {},
+                ),
+            ),
+            now,
+        ) {
+            Some(BondingEvent::Listed(x)) => {
+                assert_eq!(x.len(), 1);
+                assert_eq!(x[0].address_type, AddressType::public_identity);
+            }
+            x => panic!("Unexpected bonding event {:?}", x),
+        }
+        assert_eq!(store.find(&address).unwrap().handle, 3);
+
+        store.set_name(address, "Heart rate strap");
+        store.handle(
+            &event(
+                MessageClass::le_connection,
+                0x00,
+                MessagePayload::evt_le_connection_opened(le_connection::evt::opened {
+                    address: [0x41, 0x42, 0x43, 0x44, 0x45, 0x46],
+                    address_type: AddressType::random,
+                    master: Role::master,
+                    connection: 1,
+                    bonding: 3,
+                    advertiser: 0xff,
+                }),
+            ),
+            now,
+        );
+        assert_eq!(store.metadata(&address).unwrap().last_connected, Some(now));
+
+        let path = temp_path("bgapi_bonding_metadata_test");
+        store.save_metadata(&path).unwrap();
+        let mut loaded = BondingStore::new();
+        loaded.load_metadata(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(loaded.metadata(&address), store.metadata(&address));
+
+        // A new bonding is known by the connection address until listed.
+        let rpa = [0x41, 0x42, 0x43, 0x44, 0x45, 0x46];
+        let identity = [0x21, 0x22, 0x23, 0x24, 0x25, 0x26];
+        store.handle(
+            &event(
+                MessageClass::sm,
+                0x03,
+                MessagePayload::evt_sm_bonded(evt::bonded {
+                    connection: 1,
+                    bonding: 5,
+                }),
+            ),
+            now,
+        );
+        assert_eq!(store.find(&rpa).unwrap().handle, 5);
+        store.set_name(identity, "Scale");
+        store.list();
+        for message in [
+            response(
+                MessageClass::sm,
+                0x0b,
+                MessagePayload::rsp_sm_list_all_bondings(rsp::list_all_bondings {
+                    result: Error::success,
+                }),
+            ),
+            event(
+                MessageClass::sm,
+                0x05,
+                MessagePayload::evt_sm_list_bonding_entry(evt::list_bonding_entry {
+                    bonding: 3,
+                    address,
+                    address_type: 2,
+                }),
+            ),
+            event(
+                MessageClass::sm,
+                0x05,
+                MessagePayload::evt_sm_list_bonding_entry(evt::list_bonding_entry {
+                    bonding: 5,
+                    address: identity,
+                    address_type: 3,
+                }),
+            ),
+            event(
+                MessageClass::sm,
+                0x06,
+                MessagePayload::evt_sm_list_all_bondings_complete(
+                    evt::list_all_bondings_complete {},
+                ),
+            ),
+        ] {
+            store.handle(&message, now);
+        }
+        assert_eq!(store.metadata(&rpa), None);
+        let metadata = store.metadata(&identity).unwrap();
+        assert_eq!(metadata.name, Some(String::from("Scale")));
+        assert_eq!(metadata.last_connected, Some(now));
+
+        assert_eq!(store.delete(4), None);
+        assert!(store.delete(3).is_some());
+        match store.handle(
+            &response(
+                MessageClass::sm,
+                0x06,
+                MessagePayload::rsp_sm_delete_bonding(rsp::delete_bonding {
+                    result: Error::success,
+                }),
+            ),
+            SystemTime::now(),
+        ) {
+            Some(BondingEvent::Deleted(x)) => assert_eq!(x.address, address),
+            x => panic!("Unexpected bonding event {:?}", x),
+        }
+        assert_eq!(store.find(&address), None);
+        assert_eq!(store.metadata(&address), None);
+    }
+
+    #[test]
+    fn sm_add_to_whitelist_and_minimum_key_size() {
+        use error::Error;
+        use le_gap::AddressType;
+        use message::MessagePayload;
+        use parser::parse_next_message;
+        use sm::cmd::{add_to_whitelist, set_minimum_key_size};
+        use sm::rsp;
+
+        let message =
+            add_to_whitelist::new([0x01, 0x02, 0x03, 0x04, 0x05, 0x06], AddressType::random);
+        assert_eq!(message.header.payload_length, 0x07);
+        let bytes: Vec<u8> = message.into();
+        assert_eq!(
+            bytes,
+            vec![0x20, 0x07, 0x0f, 0x13, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01, 0x01]
+        );
+        let bytes: Vec<u8> = set_minimum_key_size::new(16).into();
+        assert_eq!(bytes, vec![0x20, 0x01, 0x0f, 0x14, 0x10]);
+
+        let (tx, rx) = spmc::channel();
+        for x in &[0x20, 0x02, 0x0f, 0x14, 0x80, 0x01] {
+            tx.send(*x).unwrap();
+        }
+        let message = parse_next_message(&rx).expect("Failed parsing message.");
+        assert_eq!(
+            message.payload,
+            MessagePayload::rsp_sm_set_minimum_key_size(rsp::set_minimum_key_size {
+                result: Error::invalid_param,
+            })
+        );
+    }
+
+    #[test]
+    fn message_evt_le_gap_extended_scan_response_anonymous_from_bytes() {
+        use le_gap::{AddressType, AdvertisingEventType, DataStatus};
+        use message::{MessageClass, MessagePayload};
+        use parser::parse_next_message;
+
+        let (tx, rx) = spmc::channel();
+        let bytes = [
+            0xa0, 0x14, 0x03, 0x04, 0xa5, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0x04,
+            0x04, 0x01, 0x7f, 0xc4, 0x25, 0x00, 0x00, 0x02, 0x01, 0x06,
+        ];
+        for x in &bytes {
+            tx.send(*x).unwrap();
+        }
+        let actual = parse_next_message(&rx).expect("Failed parsing message.");
+        assert_eq!(actual.header.message_class, MessageClass::le_gap);
+        match actual.payload {
+            MessagePayload::evt_le_gap_extended_scan_response(ref x) => {
+                assert_eq!(x.packet_type.event_type, AdvertisingEventType::reserved_5);
+                assert_eq!(x.packet_type.data_status, DataStatus::incomplete);
+                assert_eq!(x.address_type, AddressType::anonymous);
+                assert_eq!(x.data, vec![0x01, 0x06]);
+            }
+            ref x => panic!("Unexpected payload {:?}", x),
+        }
+
+        let msg: Vec<u8> = actual.into();
+        assert_eq!(msg, bytes.to_vec());
+    }
+
+    #[test]
+    fn scanner_merges_interleaved_fragments() {
+        use le_gap::evt::extended_scan_response;
+        use le_gap::scanner::{ScanEvent, ScanFilter, Scanner, ScannerConfig};
+        use le_gap::{AddressType, PhyType};
+        use message::{MessageClass, MessagePayload};
+        use std::time::Instant;
+
+        let report = |packet_type: u8, data: Vec<u8>| {
+            event(
+                MessageClass::le_gap,
+                0x04,
+                MessagePayload::evt_le_gap_extended_scan_response(extended_scan_response {
+                    packet_type: packet_type.into(),
+                    address: [0x00, 0x0b, 0x57, 0x01, 0x02, 0x03],
+                    address_type: AddressType::public,
+                    bonding: 0xff,
+                    primary_phy: PhyType::phy_1m,
+                    secondary_phy: 0x02,
+                    adv_sid: 0x01,
+                    tx_power: 0x7f,
+                    rssi: -60,
+                    channel: 37,
+                    periodic_interval: 0,
+                    data,
+                }),
+            )
+        };
+        let mut scanner = Scanner::new(ScannerConfig::default(), ScanFilter::default());
+        let now = Instant::now();
+
+        // Advertising data in two fragments with the scan response between.
+        scanner.handle(&report(0xa0, vec![0x02, 0x01, 0x06]), now);
+        scanner.handle(&report(0x84, vec![0x03, 0x09, b'A', b'B']), now);
+        match scanner.handle(&report(0x80, vec![0x03, 0x03, 0x0f, 0x18]), now) {
+            Some(ScanEvent::DeviceUpdated(device)) => {
+                assert_eq!(
+                    device.adv_data,
+                    vec![0x02, 0x01, 0x06, 0x03, 0x03, 0x0f, 0x18]
+                );
+                assert_eq!(device.scan_rsp_data, vec![0x03, 0x09, b'A', b'B']);
+            }
+            x => panic!("Unexpected scan event {:?}", x),
+        }
+
+        // A complete report replaces the data.
+        scanner.handle(&report(0x80, vec![0x02, 0x01, 0x04]), now);
+        let device = scanner
+            .device(&[0x00, 0x0b, 0x57, 0x01, 0x02, 0x03])
+            .unwrap();
+        assert_eq!(device.adv_data, vec![0x02, 0x01, 0x04]);
+    }
+
+    #[test]
+    fn advertiser_matches_stop_responses_after_remove() {
+        use error::Error;
+        use le_gap::advertiser::Advertiser;
+        use le_gap::advertiser::{AdvertisingConfig, AdvertisingEvent, AdvertisingState};
+        use le_gap::rsp::{start_advertising, stop_advertising};
+        use message::{MessageClass, MessagePayload};
+        use std::time::Duration;
+
+        let started = response(
+            MessageClass::le_gap,
+            0x14,
+            MessagePayload::rsp_le_gap_start_advertising(start_advertising {
+                result: Error::success,
+            }),
+        );
+        let stopped = response(
+            MessageClass::le_gap,
+            0x15,
+            MessagePayload::rsp_le_gap_stop_advertising(stop_advertising {
+                result: Error::success,
+            }),
+        );
+
+        // Durations beyond the 16-bit field are clamped.
+        let config = AdvertisingConfig {
+            duration: Some(Duration::from_secs(1000)),
+            ..Default::default()
+        };
+        match config.commands(0)[0].payload {
+            MessagePayload::cmd_le_gap_set_advertise_timing(ref x) => {
+                assert_eq!(x.duration, 0xffff)
+            }
+            ref x => panic!("Unexpected command {:?}", x),
+        }
+
+        let mut advertiser = Advertiser::new(2);
+        let first = advertiser.create(config.clone()).unwrap();
+        let second = advertiser.create(config).unwrap();
+        advertiser.start(first);
+        advertiser.start(second);
+        advertiser.handle(&started);
+        advertiser.handle(&started);
+
+        assert_eq!(advertiser.remove(first).len(), 1);
+        assert_eq!(advertiser.stop(second).len(), 1);
+        assert_eq!(advertiser.handle(&stopped), None);
+        assert_eq!(
+            advertiser.handle(&stopped),
+            Some(AdvertisingEvent {
+                handle: second,
+                state: AdvertisingState::Stopped,
+            })
+        );
+    }
+
+    #[test]
+    fn message_le_gap_set_conn_phy_to_and_from_bytes() {
+        use error::Error;
+        use le_gap::{cmd, rsp};
+        use message::MessagePayload;
+
+        // Either PHY may be a combination of 1M (0x01), 2M (0x02) and coded
+        // (0x04).
+        let cmd: Vec<u8> = cmd::set_conn_phy::new(0x03, 0x07).into();
+        assert_eq!(cmd, vec![0x20, 0x02, 0x03, 0x09, 0x03, 0x07]);
+        let payload = cmd::set_conn_phy::from(&cmd[4..]);
+        assert_eq!(payload.preferred_phy, 0x03);
+        assert_eq!(payload.accepted_phy, 0x07);
+
+        let actual = parse_bytes(&[0x20, 0x02, 0x03, 0x09, 0x00, 0x00]);
+        let expected = MessagePayload::rsp_le_gap_set_conn_phy(rsp::set_conn_phy {
+            result: Error::success,
+        });
+        assert_eq!(actual.payload, expected);
+    }
+
+    #[test]
+    fn message_le_gap_set_long_advertising_data_to_and_from_bytes() {
+        use error::Error;
+        use le_gap::{cmd, rsp};
+        use message::MessagePayload;
+
+        let cmd: Vec<u8> = cmd::set_long_advertising_data::new(1, 0).into();
+        assert_eq!(cmd, vec![0x20, 0x02, 0x03, 0x20, 0x01, 0x00]);
+
+        let actual = parse_bytes(&[0x20, 0x02, 0x03, 0x20, 0x80, 0x01]);
+        let expected =
+            MessagePayload::rsp_le_gap_set_long_advertising_data(rsp::set_long_advertising_data {
+                result: Error::invalid_param,
+            });
+        assert_eq!(actual.payload, expected);
+    }
+
+    #[test]
+    fn message_le_gap_enable_whitelisting_to_and_from_bytes() {
+        use error::Error;
+        use le_gap::{cmd, rsp};
+        use message::MessagePayload;
+
+        let cmd: Vec<u8> = cmd::enable_whitelisting::new(1).into();
+        assert_eq!(cmd, vec![0x20, 0x01, 0x03, 0x21, 0x01]);
+
+        let actual = parse_bytes(&[0x20, 0x02, 0x03, 0x21, 0x00, 0x00]);
+        let expected = MessagePayload::rsp_le_gap_enable_whitelisting(rsp::enable_whitelisting {
+            result: Error::success,
+        });
+        assert_eq!(actual.payload, expected);
+    }
+
+    #[test]
+    fn message_le_gap_set_conn_timing_parameters_to_and_from_bytes() {
+        use error::Error;
+        use le_gap::{cmd, rsp};
+        use message::MessagePayload;
+
+        let cmd: Vec<u8> =
+            cmd::set_conn_timing_parameters::new(0x06, 0x0c, 0, 0x64, 0, 0xffff).into();
+        assert_eq!(
+            cmd,
+            vec![
+                0x20, 0x0c, 0x03, 0x22, 0x06, 0x00, 0x0c, 0x00, 0x00, 0x00, 0x64, 0x00, 0x00, 0x00,
+                0xff, 0xff,
+            ]
+        );
+
+        let actual = parse_bytes(&[0x20, 0x02, 0x03, 0x22, 0x00, 0x00]);
+        let expected = MessagePayload::rsp_le_gap_set_conn_timing_parameters(
+            rsp::set_conn_timing_parameters {
+                result: Error::success,
+            },
+        );
+        assert_eq!(actual.payload, expected);
+    }
+
+    #[test]
+    fn message_le_gap_set_advertise_random_address_to_and_from_bytes() {
+        use error::Error;
+        use le_gap::{cmd, rsp};
+        use message::MessagePayload;
+
+        // Address type 0 is a static address.
+        let address = [0xc0, 0x11, 0x22, 0x33, 0x44, 0x55];
+        let cmd: Vec<u8> = cmd::set_advertise_random_address::new(0, 0, address).into();
+        assert_eq!(
+            cmd,
+            vec![0x20, 0x08, 0x03, 0x25, 0x00, 0x00, 0x55, 0x44, 0x33, 0x22, 0x11, 0xc0]
+        );
+
+        let bytes = [
+            0x20, 0x08, 0x03, 0x25, 0x00, 0x00, 0x55, 0x44, 0x33, 0x22, 0x11, 0xc0,
+        ];
+        let actual = parse_bytes(&bytes);
+        let expected = MessagePayload::rsp_le_gap_set_advertise_random_address(
+            rsp::set_advertise_random_address {
+                result: Error::success,
+                address_out: address,
+            },
+        );
+        assert_eq!(actual.payload, expected);
+
+        let msg: Vec<u8> = actual.into();
+        assert_eq!(msg, bytes.to_vec());
+    }
+
+    #[test]
+    fn message_le_gap_clear_advertise_random_address_to_and_from_bytes() {
+        use error::Error;
+        use le_gap::{cmd, rsp};
+        use message::MessagePayload;
+
+        let cmd: Vec<u8> = cmd::clear_advertise_random_address::new(2).into();
+        assert_eq!(cmd, vec![0x20, 0x01, 0x03, 0x26, 0x02]);
+
+        let actual = parse_bytes(&[0x20, 0x02, 0x03, 0x26, 0x00, 0x00]);
+        let expected = MessagePayload::rsp_le_gap_clear_advertise_random_address(
+            rsp::clear_advertise_random_address {
+                result: Error::success,
+            },
+        );
+        assert_eq!(actual.payload, expected);
+    }
+
+    #[test]
+    fn message_system_data_buffer_write_to_and_from_bytes() {
+        use error::Error;
+        use message::MessagePayload;
+        use system::{cmd, rsp};
+
+        let cmd: Vec<u8> = cmd::data_buffer_write::new(vec![0x02, 0x01, 0x06]).into();
+        assert_eq!(cmd, vec![0x20, 0x04, 0x01, 0x12, 0x03, 0x02, 0x01, 0x06]);
+
+        let actual = parse_bytes(&[0x20, 0x02, 0x01, 0x12, 0x00, 0x00]);
+        let expected = MessagePayload::rsp_system_data_buffer_write(rsp::data_buffer_write {
+            result: Error::success,
+        });
+        assert_eq!(actual.payload, expected);
+    }
+
+    #[test]
+    fn gatt_cache_checks_database_hash_of_peers_bonded_while_connected() {
+        use gatt::cache::{CacheEvent, DiscoveryCache};
+        use gatt::discovery::{Characteristic, Service};
+        use gatt::{evt, AttOpcode, CharacteristicProperties};
+        use le_connection::{self, Role};
+        use le_gap::AddressType;
+        use message::{MessageClass, MessagePayload};
+        use sm;
+
+        let address = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+        let services = vec![Service {
+            handle: 0x0005_0001,
+            uuid: vec![0x18, 0x01],
+            characteristics: vec![Characteristic {
+                handle: 0x0003,
+                properties: CharacteristicProperties::READ,
+                uuid: vec![0x2b, 0x2a],
+                descriptors: Vec::new(),
+            }],
+        }];
+        let hash = |value: u8| {
+            event(
+                MessageClass::gatt,
+                0x04,
+                MessagePayload::evt_gatt_characteristic_value(evt::characteristic_value {
+                    connection: 1,
+                    characteristic: 0x0003,
+                    att_opcode: AttOpcode::read_response,
+                    offset: 0,
+                    value: vec![value; 16],
+                }),
+            )
+        };
+
+        let mut cache = DiscoveryCache::new();
+        cache.insert(address, None, services.clone());
+        cache.handle(&event(
+            MessageClass::le_connection,
+            0x00,
+            MessagePayload::evt_le_connection_opened(le_connection::evt::opened {
+                address,
+                address_type: AddressType::public,
+                master: Role::master,
+                connection: 1,
+                bonding: 0xff,
+                advertiser: 0xff,
+            }),
+        ));
+        assert_eq!(cache.services(1), None);
+        assert_eq!(cache.check_database_hash(1), None);
+
+        cache.handle(&event(
+            MessageClass::sm,
+            0x03,
+            MessagePayload::evt_sm_bonded(sm::evt::bonded {
+                connection: 1,
+                bonding: 0,
+            }),
+        ));
+        assert_eq!(cache.services(1), Some(&services[..]));
+        match cache.check_database_hash(1) {
+            Some(ref x) => assert_eq!(x.header.message_id, 0x07),
+            x => panic!("Unexpected command {:?}", x),
+        }
+
+        // The first hash read is kept, a different one later drops the entry.
+        assert_eq!(cache.handle(&hash(0xaa)), None);
+        assert_eq!(
+            cache.entry(&address).unwrap().database_hash,
+            Some([0xaa; 16])
+        );
+        assert_eq!(cache.handle(&hash(0xaa)), None);
+        assert_eq!(
+            cache.handle(&hash(0xbb)),
+            Some(CacheEvent::Invalidated(address))
+        );
+        assert_eq!(cache.services(1), None);
+    }
+
+    #[test]
+    fn gatt_subscriptions_survive_for_peers_bonded_while_connected() {
+        use error::Error;
+        use gatt::subscription::SubscriptionManager;
+        use gatt::ClientConfigFlag;
+        use le_connection::{self, Role};
+        use le_gap::AddressType;
+        use message::{MessageClass, MessagePayload};
+        use sm;
+
+        let opened = |connection: u8, bonding: u8| {
+            event(
+                MessageClass::le_connection,
+                0x00,
+                MessagePayload::evt_le_connection_opened(le_connection::evt::opened {
+                    address: [0x01, 0x02, 0x03, 0x04, 0x05, 0x06],
+                    address_type: AddressType::public,
+                    master: Role::master,
+                    connection,
+                    bonding,
+                    advertiser: 0xff,
+                }),
+            )
+        };
+        let closed = |connection: u8| {
+            event(
+                MessageClass::le_connection,
+                0x01,
+                MessagePayload::evt_le_connection_closed(le_connection::evt::closed {
+                    reason: Error::success,
+                    connection,
+                }),
+            )
+        };
+
+        let mut subscriptions = SubscriptionManager::new();
+        subscriptions.handle(&opened(1, 0xff));
+        let (_receiver, _) = subscriptions
+            .subscribe(1, 0x0010, ClientConfigFlag::notification)
+            .unwrap();
+        subscriptions.handle(&event(
+            MessageClass::sm,
+            0x03,
+            MessagePayload::evt_sm_bonded(sm::evt::bonded {
+                connection: 1,
+                bonding: 0,
+            }),
+        ));
+        subscriptions.handle(&closed(1));
+
+        let commands = subscriptions.handle(&opened(2, 0));
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].header.message_id, 0x05);
+        assert!(subscriptions.is_subscribed(2, 0x0010));
+    }
+
+    #[test]
+    fn scanner_skips_anonymous_advertisers() {
+        use le_gap::evt::extended_scan_response;
+        use le_gap::scanner::{ScanFilter, Scanner, ScannerConfig};
+        use le_gap::{AddressType, PhyType};
+        use message::{MessageClass, MessagePayload};
+        use std::time::Instant;
+
+        let report = |adv_sid: u8| {
+            event(
+                MessageClass::le_gap,
+                0x04,
+                MessagePayload::evt_le_gap_extended_scan_response(extended_scan_response {
+                    packet_type: 0x80.into(),
+                    address: [0x00; 6],
+                    address_type: AddressType::anonymous,
+                    bonding: 0xff,
+                    primary_phy: PhyType::phy_1m,
+                    secondary_phy: 0x02,
+                    adv_sid,
+                    tx_power: 0x7f,
+                    rssi: -60,
+                    channel: 37,
+                    periodic_interval: 0,
+                    data: vec![0x02, 0x01, 0x06],
+                }),
+            )
+        };
+        let mut scanner = Scanner::new(ScannerConfig::default(), ScanFilter::default());
+        let now = Instant::now();
+
+        assert_eq!(scanner.handle(&report(1), now), None);
+        assert_eq!(scanner.handle(&report(2), now), None);
+        assert!(scanner.devices().is_empty());
+    }
+
+    #[test]
+    fn advertiser_fails_sets_with_rejected_configuration() {
+        use error::Error;
+        use le_gap::advertiser::Advertiser;
+        use le_gap::advertiser::{AdvertisingConfig, AdvertisingEvent, AdvertisingState};
+        use le_gap::rsp;
+        use message::{MessageClass, MessagePayload};
+        use std::time::Duration;
+
+        let mut advertiser = Advertiser::new(1);
+        let handle = advertiser.create(AdvertisingConfig::default()).unwrap();
+        assert_eq!(advertiser.start(handle).len(), 6);
+        assert_eq!(
+            advertiser.handle(&response(
+                MessageClass::le_gap,
+                0x0e,
+                MessagePayload::rsp_le_gap_set_advertise_timing(rsp::set_advertise_timing {
+                    result: Error::invalid_param,
+                }),
+            )),
+            Some(AdvertisingEvent {
+                handle,
+                state: AdvertisingState::Failed(Error::invalid_param),
+            })
+        );
+        for message in [
+            response(
+                MessageClass::le_gap,
+                0x0f,
+                MessagePayload::rsp_le_gap_set_advertise_channel_map(
+                    rsp::set_advertise_channel_map {
+                        result: Error::success,
+                    },
+                ),
+            ),
+            response(
+                MessageClass::le_gap,
+                0x11,
+                MessagePayload::rsp_le_gap_set_advertise_phy(rsp::set_advertise_phy {
+                    result: Error::success,
+                }),
+            ),
+            response(
+                MessageClass::le_gap,
+                0x13,
+                MessagePayload::rsp_le_gap_clear_advertise_configuration(
+                    rsp::clear_advertise_configuration {
+                        result: Error::success,
+                    },
+                ),
+            ),
+            response(
+                MessageClass::le_gap,
+                0x10,
+                MessagePayload::rsp_le_gap_set_advertise_report_scan_request(
+                    rsp::set_advertise_report_scan_request {
+                        result: Error::success,
+                    },
+                ),
+            ),
+            response(
+                MessageClass::le_gap,
+                0x14,
+                MessagePayload::rsp_le_gap_start_advertising(rsp::start_advertising {
+                    result: Error::success,
+                }),
+            ),
+        ] {
+            assert_eq!(advertiser.handle(&message), None);
+        }
+        assert_eq!(
+            advertiser.set(handle).unwrap().state,
+            AdvertisingState::Failed(Error::invalid_param)
+        );
+
+        // Periodic advertising is only stopped on sets it was started on.
+        assert!(advertiser.stop_periodic(handle).is_empty());
+        let interval = Duration::from_millis(100);
+        assert!(advertiser
+            .start_periodic(7, interval, interval, Vec::new())
+            .is_empty());
+        assert_eq!(
+            advertiser
+                .start_periodic(handle, interval, interval, Vec::new())
+                .len(),
+            2
+        );
+        assert_eq!(advertiser.stop_periodic(handle).len(), 1);
+        assert!(advertiser.stop_periodic(handle).is_empty());
+    }
 }
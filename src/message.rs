@@ -10,6 +10,7 @@ use le_gap;
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
 use sm;
+use sync;
 use system;
 use test;
 use user;
@@ -51,6 +52,7 @@ pub enum MessageClass {
     le_connection = 0x08,
     le_gap = 0x03,
     sm = 0x0f,
+    sync = 0x42,
     system = 0x01,
     test = 0x0e,
     user = 0xff,
@@ -196,10 +198,16 @@ pub enum MessagePayload {
     rsp_le_connection_disable_slave_latency(le_connection::rsp::disable_slave_latency),
     cmd_le_connection_get_rssi(le_connection::cmd::get_rssi),
     rsp_le_connection_get_rssi(le_connection::rsp::get_rssi),
+    cmd_le_connection_read_channel_map(le_connection::cmd::read_channel_map),
+    rsp_le_connection_read_channel_map(le_connection::rsp::read_channel_map),
     cmd_le_connection_set_parameters(le_connection::cmd::set_parameters),
     rsp_le_connection_set_parameters(le_connection::rsp::set_parameters),
     cmd_le_connection_set_phy(le_connection::cmd::set_phy),
     rsp_le_connection_set_phy(le_connection::rsp::set_phy),
+    cmd_le_connection_set_preferred_phy(le_connection::cmd::set_preferred_phy),
+    rsp_le_connection_set_preferred_phy(le_connection::rsp::set_preferred_phy),
+    cmd_le_connection_set_timing_parameters(le_connection::cmd::set_timing_parameters),
+    rsp_le_connection_set_timing_parameters(le_connection::rsp::set_timing_parameters),
     evt_le_connection_closed(le_connection::evt::closed),
     evt_le_connection_opened(le_connection::evt::opened),
     evt_le_connection_parameters(le_connection::evt::parameters),
@@ -211,8 +219,12 @@ pub enum MessagePayload {
     rsp_le_gap_bt5_set_adv_data(le_gap::rsp::bt5_set_adv_data),
     cmd_le_gap_clear_advertise_configuration(le_gap::cmd::clear_advertise_configuration),
     rsp_le_gap_clear_advertise_configuration(le_gap::rsp::clear_advertise_configuration),
+    cmd_le_gap_clear_advertise_random_address(le_gap::cmd::clear_advertise_random_address),
+    rsp_le_gap_clear_advertise_random_address(le_gap::rsp::clear_advertise_random_address),
     cmd_le_gap_connect(le_gap::cmd::connect),
     rsp_le_gap_connect(le_gap::rsp::connect),
+    cmd_le_gap_enable_whitelisting(le_gap::cmd::enable_whitelisting),
+    rsp_le_gap_enable_whitelisting(le_gap::rsp::enable_whitelisting),
     cmd_le_gap_end_procedure(le_gap::cmd::end_procedure),
     rsp_le_gap_end_procedure(le_gap::rsp::end_procedure),
     cmd_le_gap_set_advertise_channel_map(le_gap::cmd::set_advertise_channel_map),
@@ -221,6 +233,8 @@ pub enum MessagePayload {
     rsp_le_gap_set_advertise_configuration(le_gap::rsp::set_advertise_configuration),
     cmd_le_gap_set_advertise_phy(le_gap::cmd::set_advertise_phy),
     rsp_le_gap_set_advertise_phy(le_gap::rsp::set_advertise_phy),
+    cmd_le_gap_set_advertise_random_address(le_gap::cmd::set_advertise_random_address),
+    rsp_le_gap_set_advertise_random_address(le_gap::rsp::set_advertise_random_address),
     cmd_le_gap_set_advertise_report_scan_request(le_gap::cmd::set_advertise_report_scan_request),
     rsp_le_gap_set_advertise_report_scan_request(le_gap::rsp::set_advertise_report_scan_request),
     cmd_le_gap_set_advertise_timing(le_gap::cmd::set_advertise_timing),
@@ -229,25 +243,45 @@ pub enum MessagePayload {
     rsp_le_gap_set_advertise_tx_power(le_gap::rsp::set_advertise_tx_power),
     cmd_le_gap_set_conn_parameters(le_gap::cmd::set_conn_parameters),
     rsp_le_gap_set_conn_parameters(le_gap::rsp::set_conn_parameters),
+    cmd_le_gap_set_conn_phy(le_gap::cmd::set_conn_phy),
+    rsp_le_gap_set_conn_phy(le_gap::rsp::set_conn_phy),
+    cmd_le_gap_set_conn_timing_parameters(le_gap::cmd::set_conn_timing_parameters),
+    rsp_le_gap_set_conn_timing_parameters(le_gap::rsp::set_conn_timing_parameters),
     cmd_le_gap_set_data_channel_classification(le_gap::cmd::set_data_channel_classification),
     rsp_le_gap_set_data_channel_classification(le_gap::rsp::set_data_channel_classification),
+    cmd_le_gap_set_discovery_extended_scan_response(
+        le_gap::cmd::set_discovery_extended_scan_response,
+    ),
+    rsp_le_gap_set_discovery_extended_scan_response(
+        le_gap::rsp::set_discovery_extended_scan_response,
+    ),
     cmd_le_gap_set_discovery_timing(le_gap::cmd::set_discovery_timing),
     rsp_le_gap_set_discovery_timing(le_gap::rsp::set_discovery_timing),
     cmd_le_gap_set_discovery_type(le_gap::cmd::set_discovery_type),
     rsp_le_gap_set_discovery_type(le_gap::rsp::set_discovery_type),
+    cmd_le_gap_set_long_advertising_data(le_gap::cmd::set_long_advertising_data),
+    rsp_le_gap_set_long_advertising_data(le_gap::rsp::set_long_advertising_data),
     cmd_le_gap_set_privacy_mode(le_gap::cmd::set_privacy_mode),
     rsp_le_gap_set_privacy_mode(le_gap::rsp::set_privacy_mode),
     cmd_le_gap_start_advertising(le_gap::cmd::start_advertising),
     rsp_le_gap_start_advertising(le_gap::rsp::start_advertising),
     cmd_le_gap_start_discovery(le_gap::cmd::start_discovery),
     rsp_le_gap_start_discovery(le_gap::rsp::start_discovery),
+    cmd_le_gap_start_periodic_advertising(le_gap::cmd::start_periodic_advertising),
+    rsp_le_gap_start_periodic_advertising(le_gap::rsp::start_periodic_advertising),
     cmd_le_gap_stop_advertising(le_gap::cmd::stop_advertising),
     rsp_le_gap_stop_advertising(le_gap::rsp::stop_advertising),
+    cmd_le_gap_stop_periodic_advertising(le_gap::cmd::stop_periodic_advertising),
+    rsp_le_gap_stop_periodic_advertising(le_gap::rsp::stop_periodic_advertising),
     evt_le_gap_adv_timeout(le_gap::evt::adv_timeout),
+    evt_le_gap_extended_scan_response(le_gap::evt::extended_scan_response),
+    evt_le_gap_periodic_advertising_status(le_gap::evt::periodic_advertising_status),
     evt_le_gap_scan_request(le_gap::evt::scan_request),
     evt_le_gap_scan_response(le_gap::evt::scan_response),
 
     // sm
+    cmd_sm_add_to_whitelist(sm::cmd::add_to_whitelist),
+    rsp_sm_add_to_whitelist(sm::rsp::add_to_whitelist),
     cmd_sm_bonding_confirm(sm::cmd::bonding_confirm),
     rsp_sm_bonding_confirm(sm::rsp::bonding_confirm),
     cmd_sm_configure(sm::cmd::configure),
@@ -268,6 +302,8 @@ pub enum MessagePayload {
     rsp_sm_set_bondable_mode(sm::rsp::set_bondable_mode),
     cmd_sm_set_debug_mode(sm::cmd::set_debug_mode),
     rsp_sm_set_debug_mode(sm::rsp::set_debug_mode),
+    cmd_sm_set_minimum_key_size(sm::cmd::set_minimum_key_size),
+    rsp_sm_set_minimum_key_size(sm::rsp::set_minimum_key_size),
     cmd_sm_set_oob_data(sm::cmd::set_oob_data),
     rsp_sm_set_oob_data(sm::rsp::set_oob_data),
     cmd_sm_set_passkey(sm::cmd::set_passkey),
@@ -287,7 +323,18 @@ pub enum MessagePayload {
     evt_sm_passkey_display(sm::evt::passkey_display),
     evt_sm_passkey_request(sm::evt::passkey_request),
 
+    // sync
+    cmd_sync_close(sync::cmd::close),
+    rsp_sync_close(sync::rsp::close),
+    cmd_sync_open(sync::cmd::open),
+    rsp_sync_open(sync::rsp::open),
+    evt_sync_closed(sync::evt::closed),
+    evt_sync_data(sync::evt::data),
+    evt_sync_opened(sync::evt::opened),
+
     // system
+    cmd_system_data_buffer_write(system::cmd::data_buffer_write),
+    rsp_system_data_buffer_write(system::rsp::data_buffer_write),
     cmd_system_get_bt_address(system::cmd::get_bt_address),
     rsp_system_get_bt_address(system::rsp::get_bt_address),
     cmd_system_get_counters(system::cmd::get_counters),
@@ -443,10 +490,16 @@ impl Into<Vec<u8>> for MessagePayload {
             rsp_le_connection_disable_slave_latency(x) => x.into(),
             cmd_le_connection_get_rssi(x) => x.into(),
             rsp_le_connection_get_rssi(x) => x.into(),
+            cmd_le_connection_read_channel_map(x) => x.into(),
+            rsp_le_connection_read_channel_map(x) => x.into(),
             cmd_le_connection_set_parameters(x) => x.into(),
             rsp_le_connection_set_parameters(x) => x.into(),
             cmd_le_connection_set_phy(x) => x.into(),
             rsp_le_connection_set_phy(x) => x.into(),
+            cmd_le_connection_set_preferred_phy(x) => x.into(),
+            rsp_le_connection_set_preferred_phy(x) => x.into(),
+            cmd_le_connection_set_timing_parameters(x) => x.into(),
+            rsp_le_connection_set_timing_parameters(x) => x.into(),
             evt_le_connection_closed(x) => x.into(),
             evt_le_connection_opened(x) => x.into(),
             evt_le_connection_parameters(x) => x.into(),
@@ -458,8 +511,12 @@ impl Into<Vec<u8>> for MessagePayload {
             rsp_le_gap_bt5_set_adv_data(x) => x.into(),
             cmd_le_gap_clear_advertise_configuration(x) => x.into(),
             rsp_le_gap_clear_advertise_configuration(x) => x.into(),
+            cmd_le_gap_clear_advertise_random_address(x) => x.into(),
+            rsp_le_gap_clear_advertise_random_address(x) => x.into(),
             cmd_le_gap_connect(x) => x.into(),
             rsp_le_gap_connect(x) => x.into(),
+            cmd_le_gap_enable_whitelisting(x) => x.into(),
+            rsp_le_gap_enable_whitelisting(x) => x.into(),
             cmd_le_gap_end_procedure(x) => x.into(),
             rsp_le_gap_end_procedure(x) => x.into(),
             cmd_le_gap_set_advertise_channel_map(x) => x.into(),
@@ -468,6 +525,8 @@ impl Into<Vec<u8>> for MessagePayload {
             rsp_le_gap_set_advertise_configuration(x) => x.into(),
             cmd_le_gap_set_advertise_phy(x) => x.into(),
             rsp_le_gap_set_advertise_phy(x) => x.into(),
+            cmd_le_gap_set_advertise_random_address(x) => x.into(),
+            rsp_le_gap_set_advertise_random_address(x) => x.into(),
             cmd_le_gap_set_advertise_report_scan_request(x) => x.into(),
             rsp_le_gap_set_advertise_report_scan_request(x) => x.into(),
             cmd_le_gap_set_advertise_timing(x) => x.into(),
@@ -476,25 +535,41 @@ impl Into<Vec<u8>> for MessagePayload {
             rsp_le_gap_set_advertise_tx_power(x) => x.into(),
             cmd_le_gap_set_conn_parameters(x) => x.into(),
             rsp_le_gap_set_conn_parameters(x) => x.into(),
+            cmd_le_gap_set_conn_phy(x) => x.into(),
+            rsp_le_gap_set_conn_phy(x) => x.into(),
+            cmd_le_gap_set_conn_timing_parameters(x) => x.into(),
+            rsp_le_gap_set_conn_timing_parameters(x) => x.into(),
             cmd_le_gap_set_data_channel_classification(x) => x.into(),
             rsp_le_gap_set_data_channel_classification(x) => x.into(),
+            cmd_le_gap_set_discovery_extended_scan_response(x) => x.into(),
+            rsp_le_gap_set_discovery_extended_scan_response(x) => x.into(),
             cmd_le_gap_set_discovery_timing(x) => x.into(),
             rsp_le_gap_set_discovery_timing(x) => x.into(),
             cmd_le_gap_set_discovery_type(x) => x.into(),
             rsp_le_gap_set_discovery_type(x) => x.into(),
+            cmd_le_gap_set_long_advertising_data(x) => x.into(),
+            rsp_le_gap_set_long_advertising_data(x) => x.into(),
             cmd_le_gap_set_privacy_mode(x) => x.into(),
             rsp_le_gap_set_privacy_mode(x) => x.into(),
             cmd_le_gap_start_advertising(x) => x.into(),
             rsp_le_gap_start_advertising(x) => x.into(),
             cmd_le_gap_start_discovery(x) => x.into(),
             rsp_le_gap_start_discovery(x) => x.into(),
+            cmd_le_gap_start_periodic_advertising(x) => x.into(),
+            rsp_le_gap_start_periodic_advertising(x) => x.into(),
             cmd_le_gap_stop_advertising(x) => x.into(),
             rsp_le_gap_stop_advertising(x) => x.into(),
+            cmd_le_gap_stop_periodic_advertising(x) => x.into(),
+            rsp_le_gap_stop_periodic_advertising(x) => x.into(),
             evt_le_gap_adv_timeout(x) => x.into(),
+            evt_le_gap_extended_scan_response(x) => x.into(),
+            evt_le_gap_periodic_advertising_status(x) => x.into(),
             evt_le_gap_scan_request(x) => x.into(),
             evt_le_gap_scan_response(x) => x.into(),
 
             // sm
+            cmd_sm_add_to_whitelist(x) => x.into(),
+            rsp_sm_add_to_whitelist(x) => x.into(),
             cmd_sm_bonding_confirm(x) => x.into(),
             rsp_sm_bonding_confirm(x) => x.into(),
             cmd_sm_configure(x) => x.into(),
@@ -515,6 +590,8 @@ impl Into<Vec<u8>> for MessagePayload {
             rsp_sm_set_bondable_mode(x) => x.into(),
             cmd_sm_set_debug_mode(x) => x.into(),
             rsp_sm_set_debug_mode(x) => x.into(),
+            cmd_sm_set_minimum_key_size(x) => x.into(),
+            rsp_sm_set_minimum_key_size(x) => x.into(),
             cmd_sm_set_oob_data(x) => x.into(),
             rsp_sm_set_oob_data(x) => x.into(),
             cmd_sm_set_passkey(x) => x.into(),
@@ -534,7 +611,18 @@ impl Into<Vec<u8>> for MessagePayload {
             evt_sm_passkey_display(x) => x.into(),
             evt_sm_passkey_request(x) => x.into(),
 
+            // sync
+            cmd_sync_close(x) => x.into(),
+            rsp_sync_close(x) => x.into(),
+            cmd_sync_open(x) => x.into(),
+            rsp_sync_open(x) => x.into(),
+            evt_sync_closed(x) => x.into(),
+            evt_sync_data(x) => x.into(),
+            evt_sync_opened(x) => x.into(),
+
             // system
+            cmd_system_data_buffer_write(x) => x.into(),
+            rsp_system_data_buffer_write(x) => x.into(),
             cmd_system_get_bt_address(x) => x.into(),
             rsp_system_get_bt_address(x) => x.into(),
             cmd_system_get_counters(x) => x.into(),
@@ -9,6 +9,7 @@ use le_gap;
 use message::{Message, MessageClass, MessageHeader};
 use sm;
 use std::io::{Error, ErrorKind};
+use sync;
 use system;
 use test;
 use user;
@@ -42,6 +43,7 @@ pub fn parse_next_message(stream: &Stream) -> Result<Message, Error> {
         MessageClass::le_connection => le_connection::parse(&header, buffer.as_slice())?,
         MessageClass::le_gap => le_gap::parse(&header, buffer.as_slice())?,
         MessageClass::sm => sm::parse(&header, buffer.as_slice())?,
+        MessageClass::sync => sync::parse(&header, buffer.as_slice())?,
         MessageClass::system => system::parse(&header, buffer.as_slice())?,
         MessageClass::test => test::parse(&header, buffer.as_slice())?,
         MessageClass::user => user::parse(&header, buffer.as_slice())?,
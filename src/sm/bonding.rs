@@ -0,0 +1,269 @@
+use error::Error;
+use hex::{from_hex, to_hex};
+use le_gap::AddressType;
+use message::{Message, MessagePayload};
+use num_traits::FromPrimitive;
+use sm::cmd::{delete_bonding, delete_bondings, list_all_bondings};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, BufWriter, ErrorKind, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const NO_BONDING: u8 = 0xff;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bonding {
+    pub handle: u8,
+    pub address: [u8; 6], // Identity address of the peer
+    pub address_type: AddressType,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BondingMetadata {
+    pub name: Option<String>,
+    pub last_connected: Option<SystemTime>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BondingEvent {
+    Listed(Vec<Bonding>),
+    Deleted(Bonding),
+    DeletedAll,
+    Failed(Error),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operation {
+    List,
+    Delete(u8),
+    DeleteAll,
+}
+
+// The bondings stored by the stack, as last listed, with metadata kept by
+// the host for each peer.
+#[derive(Debug, Default)]
+pub struct BondingStore {
+    bondings: BTreeMap<u8, Bonding>,
+    metadata: HashMap<[u8; 6], BondingMetadata>,
+    listing: Vec<Bonding>,
+    pending: VecDeque<Operation>, // Commands awaiting their response
+    connections: HashMap<u8, ([u8; 6], AddressType)>,
+}
+
+impl BondingStore {
+    pub fn new() -> BondingStore {
+        BondingStore::default()
+    }
+
+    pub fn list(&mut self) -> Message {
+        self.pending.push_back(Operation::List);
+        list_all_bondings::new()
+    }
+
+    pub fn bondings(&self) -> Vec<&Bonding> {
+        self.bondings.values().collect()
+    }
+
+    pub fn get(&self, handle: u8) -> Option<&Bonding> {
+        self.bondings.get(&handle)
+    }
+
+    pub fn find(&self, address: &[u8; 6]) -> Option<&Bonding> {
+        self.bondings.values().find(|x| x.address == *address)
+    }
+
+    pub fn delete(&mut self, handle: u8) -> Option<Message> {
+        self.bondings.get(&handle)?;
+        self.pending.push_back(Operation::Delete(handle));
+        Some(delete_bonding::new(handle))
+    }
+
+    pub fn delete_all(&mut self) -> Message {
+        self.pending.push_back(Operation::DeleteAll);
+        delete_bondings::new()
+    }
+
+    pub fn metadata(&self, address: &[u8; 6]) -> Option<&BondingMetadata> {
+        self.metadata.get(address)
+    }
+
+    pub fn set_name(&mut self, address: [u8; 6], name: &str) {
+        self.metadata.entry(address).or_default().name = Some(name.to_string());
+    }
+
+    // Connections to bonded peers update the time they were last connected.
+    pub fn handle(&mut self, message: &Message, now: SystemTime) -> Option<BondingEvent> {
+        match message.payload {
+            MessagePayload::rsp_sm_list_all_bondings(ref x) => {
+                self.response(&Operation::List)?;
+                if x.result != Error::success {
+                    return Some(BondingEvent::Failed(x.result.clone()));
+                }
+                self.listing.clear();
+                None
+            }
+            // Entries with an address type this crate does not know are
+            // left out.
+            MessagePayload::evt_sm_list_bonding_entry(ref x) => {
+                let address_type = FromPrimitive::from_u8(x.address_type)?;
+                self.listing.push(Bonding {
+                    handle: x.bonding,
+                    address: x.address,
+                    address_type,
+                });
+                None
+            }
+            MessagePayload::evt_sm_list_all_bondings_complete(_) => {
+                let listing: Vec<Bonding> = self.listing.drain(..).collect();
+                // Bondings made since the last listing were known by the
+                // address of the connection, their metadata moves to the
+                // identity address.
+                for bonding in &listing {
+                    let previous = match self.bondings.get(&bonding.handle) {
+                        Some(x) if x.address != bonding.address => x.address,
+                        _ => continue,
+                    };
+                    if let Some(old) = self.metadata.remove(&previous) {
+                        let new = self.metadata.entry(bonding.address).or_default();
+                        if new.name.is_none() {
+                            new.name = old.name;
+                        }
+                        new.last_connected = new.last_connected.max(old.last_connected);
+                    }
+                }
+                self.bondings = listing.iter().map(|x| (x.handle, x.clone())).collect();
+                Some(BondingEvent::Listed(listing))
+            }
+            MessagePayload::rsp_sm_delete_bonding(ref x) => {
+                let handle = match self.pending.front() {
+                    Some(Operation::Delete(handle)) => *handle,
+                    _ => return None,
+                };
+                self.pending.pop_front();
+                if x.result != Error::success {
+                    return Some(BondingEvent::Failed(x.result.clone()));
+                }
+                let bonding = self.bondings.remove(&handle)?;
+                self.metadata.remove(&bonding.address);
+                Some(BondingEvent::Deleted(bonding))
+            }
+            MessagePayload::rsp_sm_delete_bondings(ref x) => {
+                self.response(&Operation::DeleteAll)?;
+                if x.result != Error::success {
+                    return Some(BondingEvent::Failed(x.result.clone()));
+                }
+                self.bondings.clear();
+                self.metadata.clear();
+                Some(BondingEvent::DeletedAll)
+            }
+            MessagePayload::evt_le_connection_opened(ref x) => {
+                self.connections
+                    .insert(x.connection, (x.address, x.address_type.clone()));
+                if x.bonding != NO_BONDING {
+                    self.connected(x.bonding, now);
+                }
+                None
+            }
+            MessagePayload::evt_le_connection_closed(ref x) => {
+                self.connections.remove(&x.connection);
+                None
+            }
+            // A new bonding is known by the address the peer connected with
+            // until the bondings are listed again.
+            MessagePayload::evt_sm_bonded(ref x) if x.bonding != NO_BONDING => {
+                let (address, address_type) = self.connections.get(&x.connection)?.clone();
+                self.bondings.insert(
+                    x.bonding,
+                    Bonding {
+                        handle: x.bonding,
+                        address,
+                        address_type,
+                    },
+                );
+                self.connected(x.bonding, now);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    // Each peer is stored on a line with its address in hexadecimal, the
+    // last connection time in seconds since the Unix epoch or `-`, and the
+    // name, if any, as the rest of the line.
+    pub fn save_metadata<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        let mut entries: Vec<(&[u8; 6], &BondingMetadata)> = self.metadata.iter().collect();
+        entries.sort_by_key(|x| *x.0);
+        for (address, metadata) in entries {
+            let address = to_hex(address);
+            let time = match metadata.last_connected {
+                Some(x) => x
+                    .duration_since(UNIX_EPOCH)
+                    .map(|x| x.as_secs().to_string())
+                    .unwrap_or_else(|_| String::from("-")),
+                None => String::from("-"),
+            };
+            match metadata.name {
+                Some(ref name) => {
+                    writeln!(file, "{} {} {}", address, time, name.replace('\n', " "))?
+                }
+                None => writeln!(file, "{} {}", address, time)?,
+            }
+        }
+        file.flush()
+    }
+
+    pub fn load_metadata<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let file = BufReader::new(File::open(path)?);
+        for line in file.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields: Vec<&str> = line.splitn(3, ' ').collect();
+            let bytes = from_hex(fields[0]).unwrap_or_default();
+            if fields.len() < 2 || bytes.len() != 6 {
+                return Err(invalid_data());
+            }
+            let mut address = [0; 6];
+            address.copy_from_slice(&bytes);
+            let last_connected = match fields[1] {
+                "-" => None,
+                x => Some(
+                    UNIX_EPOCH + Duration::from_secs(x.parse::<u64>().map_err(|_| invalid_data())?),
+                ),
+            };
+            self.metadata.insert(
+                address,
+                BondingMetadata {
+                    name: fields.get(2).map(|x| x.to_string()),
+                    last_connected,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    fn response(&mut self, operation: &Operation) -> Option<()> {
+        if self.pending.front() != Some(operation) {
+            return None;
+        }
+        self.pending.pop_front();
+        Some(())
+    }
+
+    fn connected(&mut self, handle: u8, now: SystemTime) {
+        if let Some(bonding) = self.bondings.get(&handle) {
+            self.metadata
+                .entry(bonding.address)
+                .or_default()
+                .last_connected = Some(now);
+        }
+    }
+}
+
+fn invalid_data() -> io::Error {
+    io::Error::from(ErrorKind::InvalidData)
+}
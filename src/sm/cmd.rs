@@ -1,7 +1,59 @@
 use bytes::{Buf, BufMut};
+use le_gap::AddressType;
 use message::{Message, MessageClass, MessageHeader, MessagePayload, MessageType};
+use num_traits::FromPrimitive;
+use sm::{io_capability, BondingPolicy, ConfigureFlags};
 use std::io::{Cursor, Read};
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct add_to_whitelist {
+    pub address: [u8; 6],
+    pub address_type: AddressType,
+}
+
+impl add_to_whitelist {
+    pub fn new(address: [u8; 6], address_type: AddressType) -> Message {
+        let header = MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x07,
+            message_class: MessageClass::sm,
+            message_id: 0x13,
+        };
+        let payload = add_to_whitelist {
+            address,
+            address_type,
+        };
+        let payload = MessagePayload::cmd_sm_add_to_whitelist(payload);
+        Message { header, payload }
+    }
+}
+
+impl From<&[u8]> for add_to_whitelist {
+    fn from(data: &[u8]) -> add_to_whitelist {
+        let mut cursor = Cursor::new(data);
+        let mut address: [u8; 6] = Default::default();
+        cursor
+            .read_exact(&mut address)
+            .expect("Failed to read bytes.");
+        address.reverse();
+        let address_type = FromPrimitive::from_u8(cursor.get_u8()).unwrap();
+        add_to_whitelist {
+            address,
+            address_type,
+        }
+    }
+}
+
+impl Into<Vec<u8>> for add_to_whitelist {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend(self.address.iter().rev());
+        bytes.put_u8(self.address_type.clone() as u8);
+        bytes
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct bonding_confirm {
@@ -48,12 +100,12 @@ impl Into<Vec<u8>> for bonding_confirm {
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct configure {
-    pub flags: u8,
-    pub io_capabilities: u8,
+    pub flags: ConfigureFlags,
+    pub io_capabilities: io_capability,
 }
 
 impl configure {
-    pub fn new(flags: u8, io_capabilities: u8) -> Message {
+    pub fn new(flags: ConfigureFlags, io_capabilities: io_capability) -> Message {
         let header = MessageHeader {
             message_type: MessageType::command_response,
             payload_length: 0x02,
@@ -73,8 +125,8 @@ impl From<&[u8]> for configure {
     fn from(data: &[u8]) -> configure {
         let mut cursor = Cursor::new(data);
         configure {
-            flags: cursor.get_u8(),
-            io_capabilities: cursor.get_u8(),
+            flags: ConfigureFlags(cursor.get_u8()),
+            io_capabilities: FromPrimitive::from_u8(cursor.get_u8()).unwrap(),
         }
     }
 }
@@ -82,8 +134,8 @@ impl From<&[u8]> for configure {
 impl Into<Vec<u8>> for configure {
     fn into(self) -> Vec<u8> {
         let mut bytes = Vec::new();
-        bytes.put_u8(self.flags);
-        bytes.put_u8(self.io_capabilities);
+        bytes.put_u8(self.flags.0);
+        bytes.put_u8(self.io_capabilities.clone() as u8);
         bytes
     }
 }
@@ -375,6 +427,43 @@ impl Into<Vec<u8>> for set_debug_mode {
     }
 }
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct set_minimum_key_size {
+    pub minimum_key_size: u8,
+}
+
+impl set_minimum_key_size {
+    pub fn new(minimum_key_size: u8) -> Message {
+        let header = MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x01,
+            message_class: MessageClass::sm,
+            message_id: 0x14,
+        };
+        let payload = set_minimum_key_size { minimum_key_size };
+        let payload = MessagePayload::cmd_sm_set_minimum_key_size(payload);
+        Message { header, payload }
+    }
+}
+
+impl From<&[u8]> for set_minimum_key_size {
+    fn from(data: &[u8]) -> set_minimum_key_size {
+        let mut cursor = Cursor::new(data);
+        set_minimum_key_size {
+            minimum_key_size: cursor.get_u8(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for set_minimum_key_size {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u8(self.minimum_key_size);
+        bytes
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct set_oob_data {
@@ -490,11 +579,11 @@ impl Into<Vec<u8>> for set_sc_remote_oob_data {
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct store_bonding_configuration {
     pub max_bonding_count: u8,
-    pub policy_flags: u8,
+    pub policy_flags: BondingPolicy,
 }
 
 impl store_bonding_configuration {
-    pub fn new(max_bonding_count: u8, policy_flags: u8) -> Message {
+    pub fn new(max_bonding_count: u8, policy_flags: BondingPolicy) -> Message {
         let header = MessageHeader {
             message_type: MessageType::command_response,
             payload_length: 0x02,
@@ -515,7 +604,7 @@ impl From<&[u8]> for store_bonding_configuration {
         let mut cursor = Cursor::new(data);
         store_bonding_configuration {
             max_bonding_count: cursor.get_u8(),
-            policy_flags: cursor.get_u8(),
+            policy_flags: FromPrimitive::from_u8(cursor.get_u8()).unwrap(),
         }
     }
 }
@@ -524,7 +613,7 @@ impl Into<Vec<u8>> for store_bonding_configuration {
     fn into(self) -> Vec<u8> {
         let mut bytes = Vec::new();
         bytes.put_u8(self.max_bonding_count);
-        bytes.put_u8(self.policy_flags);
+        bytes.put_u8(self.policy_flags.clone() as u8);
         bytes
     }
 }
@@ -1,13 +1,25 @@
+pub mod bonding;
 pub mod cmd;
 pub mod evt;
+pub mod pairing;
 pub mod rsp;
 
 use message::{MessageClass, MessageHeader, MessagePayload, MessageType};
 use num_derive::FromPrimitive;
 use std::io::{Error, ErrorKind};
+use std::ops::BitOr;
 
 pub fn parse(header: &MessageHeader, buffer: &[u8]) -> Result<MessagePayload, Error> {
     match header {
+        MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x02,
+            message_class: MessageClass::sm,
+            message_id: 0x13,
+        } => Ok(MessagePayload::rsp_sm_add_to_whitelist(
+            rsp::add_to_whitelist::from(buffer),
+        )),
+
         MessageHeader {
             message_type: MessageType::command_response,
             payload_length: 0x02,
@@ -98,6 +110,15 @@ pub fn parse(header: &MessageHeader, buffer: &[u8]) -> Result<MessagePayload, Er
             rsp::set_debug_mode::from(buffer),
         )),
 
+        MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x02,
+            message_class: MessageClass::sm,
+            message_id: 0x14,
+        } => Ok(MessagePayload::rsp_sm_set_minimum_key_size(
+            rsp::set_minimum_key_size::from(buffer),
+        )),
+
         MessageHeader {
             message_type: MessageType::command_response,
             payload_length: 0x02,
@@ -230,7 +251,7 @@ pub enum BondingKey {
 }
 
 #[allow(non_camel_case_types)]
-#[derive(Debug, Clone, FromPrimitive)]
+#[derive(Debug, Clone, FromPrimitive, PartialEq, PartialOrd)]
 pub enum io_capability {
     displayonly = 0,     // Display Only
     displayyesno = 1,    // Display with Yes/No-buttons
@@ -238,3 +259,39 @@ pub enum io_capability {
     noinputnooutput = 3, // No Input and No Output
     keyboarddisplay = 4, // Display with Keyboard
 }
+
+// Flags of sm_configure.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct ConfigureFlags(pub u8);
+
+impl ConfigureFlags {
+    pub const BONDING_REQUIRES_MITM: ConfigureFlags = ConfigureFlags(0x01);
+    // Unbonded peers cannot encrypt
+    pub const ENCRYPTION_REQUIRES_BONDING: ConfigureFlags = ConfigureFlags(0x02);
+    pub const SECURE_CONNECTIONS_ONLY: ConfigureFlags = ConfigureFlags(0x04);
+    // Asked with evt_sm_confirm_bonding
+    pub const CONFIRM_BONDING_REQUESTS: ConfigureFlags = ConfigureFlags(0x08);
+    // Reject unbonded peers
+    pub const BONDED_DEVICES_ONLY: ConfigureFlags = ConfigureFlags(0x10);
+
+    pub fn contains(&self, other: ConfigureFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for ConfigureFlags {
+    type Output = ConfigureFlags;
+
+    fn bitor(self, other: ConfigureFlags) -> ConfigureFlags {
+        ConfigureFlags(self.0 | other.0)
+    }
+}
+
+// Policy of sm_store_bonding_configuration when the bonding database is full.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, FromPrimitive, PartialEq, PartialOrd)]
+pub enum BondingPolicy {
+    reject_new = 0,                    // New bondings fail
+    overwrite_oldest = 1,              // Oldest by creation
+    overwrite_least_recently_used = 2, // Oldest by last use
+}
@@ -0,0 +1,292 @@
+use error::Error;
+use le_connection::Security;
+use message::{Message, MessagePayload};
+use sm::cmd::{
+    bonding_confirm, configure, enter_passkey, increase_security, passkey_confirm,
+    set_bondable_mode,
+};
+use sm::{io_capability, ConfigureFlags};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::time::{Duration, Instant};
+
+const NO_BONDING: u8 = 0xff;
+
+// User interaction during pairing. Returning None leaves the answer to a
+// later call of the matching PairingManager method, for user interfaces that
+// cannot block.
+pub trait PairingUi {
+    fn display_passkey(&mut self, _connection: u8, _passkey: u32) {}
+
+    fn request_passkey(&mut self, _connection: u8) -> Option<u32> {
+        None
+    }
+
+    // Numeric comparison, true if both devices show the same passkey.
+    fn confirm_passkey(&mut self, _connection: u8, _passkey: u32) -> Option<bool> {
+        None
+    }
+
+    fn confirm_bonding(&mut self, _connection: u8) -> Option<bool> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairingConfig {
+    pub flags: ConfigureFlags,
+    pub io_capabilities: io_capability,
+    pub bondable: bool,
+    pub timeout: Duration, // Time allowed for the whole pairing, answers included
+}
+
+impl Default for PairingConfig {
+    fn default() -> PairingConfig {
+        PairingConfig {
+            flags: ConfigureFlags::default(),
+            io_capabilities: io_capability::noinputnooutput,
+            bondable: true,
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PairingEvent {
+    Command(Message), // Command to send
+    Paired {
+        connection: u8,
+        bonding: Option<u8>, // None if the keys were not stored
+        security: Security,
+    },
+    Failed(u8, Error),
+    TimedOut(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PairingState {
+    Pairing,
+    AwaitingPasskey,
+    AwaitingComparison,
+    AwaitingBonding,
+}
+
+// Drives pairing on each connection, whether requested locally with pair()
+// or started by the peer, and asks the user interface for any input.
+pub struct PairingManager {
+    config: PairingConfig,
+    ui: Box<dyn PairingUi>,
+    pairings: BTreeMap<u8, (PairingState, Instant)>, // State and deadline
+    security: BTreeMap<u8, Security>,
+    requested: VecDeque<u8>, // Connections of the increase_security awaiting their response
+    timed_out: BTreeSet<u8>, // Connections whose pairing was given up but not yet ended
+}
+
+impl PairingManager {
+    pub fn new(config: PairingConfig, ui: Box<dyn PairingUi>) -> PairingManager {
+        PairingManager {
+            config,
+            ui,
+            pairings: BTreeMap::new(),
+            security: BTreeMap::new(),
+            requested: VecDeque::new(),
+            timed_out: BTreeSet::new(),
+        }
+    }
+
+    // Commands to apply the configuration, sent before any connection.
+    pub fn setup(&self) -> Vec<Message> {
+        vec![
+            configure::new(self.config.flags, self.config.io_capabilities.clone()),
+            set_bondable_mode::new(self.config.bondable as u8),
+        ]
+    }
+
+    pub fn state(&self, connection: u8) -> Option<PairingState> {
+        self.pairings.get(&connection).map(|x| x.0)
+    }
+
+    pub fn pair(&mut self, connection: u8, now: Instant) -> Message {
+        self.timed_out.remove(&connection);
+        self.start(connection, now);
+        self.requested.push_back(connection);
+        increase_security::new(connection)
+    }
+
+    pub fn enter_passkey(&mut self, connection: u8, passkey: u32) -> Option<Message> {
+        self.answered(connection, PairingState::AwaitingPasskey)?;
+        Some(enter_passkey::new(connection, passkey as i32))
+    }
+
+    pub fn confirm_passkey(&mut self, connection: u8, confirm: bool) -> Option<Message> {
+        self.answered(connection, PairingState::AwaitingComparison)?;
+        Some(passkey_confirm::new(connection, confirm as u8))
+    }
+
+    pub fn confirm_bonding(&mut self, connection: u8, confirm: bool) -> Option<Message> {
+        self.answered(connection, PairingState::AwaitingBonding)?;
+        Some(bonding_confirm::new(connection, confirm as u8))
+    }
+
+    // Gives up on pairings past their deadline. A question left unanswered
+    // is rejected so the stack fails the pairing.
+    pub fn poll(&mut self, now: Instant) -> Vec<PairingEvent> {
+        let expired: Vec<(u8, PairingState)> = self
+            .pairings
+            .iter()
+            .filter(|x| (x.1).1 <= now)
+            .map(|x| (*x.0, (x.1).0))
+            .collect();
+        let mut events = Vec::new();
+        for (connection, state) in expired {
+            self.pairings.remove(&connection);
+            self.timed_out.insert(connection);
+            match state {
+                PairingState::AwaitingComparison => {
+                    events.push(PairingEvent::Command(passkey_confirm::new(connection, 0)))
+                }
+                PairingState::AwaitingBonding => {
+                    events.push(PairingEvent::Command(bonding_confirm::new(connection, 0)))
+                }
+                _ => (),
+            }
+            events.push(PairingEvent::TimedOut(connection));
+        }
+        events
+    }
+
+    pub fn handle(&mut self, message: &Message, now: Instant) -> Option<PairingEvent> {
+        // Whatever the stack still reports about a pairing already reported
+        // as timed out is dropped, up to the event ending it.
+        match message.payload {
+            MessagePayload::evt_sm_bonded(ref x) if self.timed_out.remove(&x.connection) => {
+                return None
+            }
+            MessagePayload::evt_sm_bonding_failed(ref x)
+                if self.timed_out.remove(&x.connection) =>
+            {
+                return None
+            }
+            MessagePayload::evt_sm_passkey_display(ref x)
+                if self.timed_out.contains(&x.connection) =>
+            {
+                return None
+            }
+            MessagePayload::evt_sm_passkey_request(ref x)
+                if self.timed_out.contains(&x.connection) =>
+            {
+                return None
+            }
+            MessagePayload::evt_sm_confirm_passkey(ref x)
+                if self.timed_out.contains(&x.connection) =>
+            {
+                return None
+            }
+            MessagePayload::evt_sm_confirm_bonding(ref x)
+                if self.timed_out.contains(&x.connection) =>
+            {
+                return None
+            }
+            _ => (),
+        }
+        match message.payload {
+            MessagePayload::evt_sm_passkey_display(ref x) => {
+                self.start(x.connection, now);
+                self.ui.display_passkey(x.connection, x.passkey);
+                None
+            }
+            MessagePayload::evt_sm_passkey_request(ref x) => {
+                self.start(x.connection, now);
+                match self.ui.request_passkey(x.connection) {
+                    Some(passkey) => Some(PairingEvent::Command(enter_passkey::new(
+                        x.connection,
+                        passkey as i32,
+                    ))),
+                    None => self.wait(x.connection, PairingState::AwaitingPasskey),
+                }
+            }
+            MessagePayload::evt_sm_confirm_passkey(ref x) => {
+                self.start(x.connection, now);
+                match self.ui.confirm_passkey(x.connection, x.passkey) {
+                    Some(confirm) => Some(PairingEvent::Command(passkey_confirm::new(
+                        x.connection,
+                        confirm as u8,
+                    ))),
+                    None => self.wait(x.connection, PairingState::AwaitingComparison),
+                }
+            }
+            MessagePayload::evt_sm_confirm_bonding(ref x) => {
+                self.start(x.connection, now);
+                match self.ui.confirm_bonding(x.connection) {
+                    Some(confirm) => Some(PairingEvent::Command(bonding_confirm::new(
+                        x.connection,
+                        confirm as u8,
+                    ))),
+                    None => self.wait(x.connection, PairingState::AwaitingBonding),
+                }
+            }
+            MessagePayload::evt_sm_bonded(ref x) => {
+                self.pairings.remove(&x.connection);
+                Some(PairingEvent::Paired {
+                    connection: x.connection,
+                    bonding: if x.bonding == NO_BONDING {
+                        None
+                    } else {
+                        Some(x.bonding)
+                    },
+                    security: self
+                        .security
+                        .get(&x.connection)
+                        .cloned()
+                        .unwrap_or(Security::mode1_level1),
+                })
+            }
+            MessagePayload::evt_sm_bonding_failed(ref x) => {
+                self.pairings.remove(&x.connection);
+                Some(PairingEvent::Failed(x.connection, x.reason.clone()))
+            }
+            MessagePayload::rsp_sm_increase_security(ref x) => {
+                let connection = self.requested.pop_front()?;
+                if x.result == Error::success || self.timed_out.contains(&connection) {
+                    return None;
+                }
+                self.pairings.remove(&connection);
+                Some(PairingEvent::Failed(connection, x.result.clone()))
+            }
+            // The security level is reported before evt_sm_bonded.
+            MessagePayload::evt_le_connection_parameters(ref x) => {
+                self.security.insert(x.connection, x.security_mode.clone());
+                None
+            }
+            MessagePayload::evt_le_connection_closed(ref x) => {
+                self.security.remove(&x.connection);
+                self.pairings.remove(&x.connection);
+                self.timed_out.remove(&x.connection);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn start(&mut self, connection: u8, now: Instant) {
+        let deadline = now + self.config.timeout;
+        self.pairings
+            .entry(connection)
+            .or_insert((PairingState::Pairing, deadline));
+    }
+
+    fn wait(&mut self, connection: u8, state: PairingState) -> Option<PairingEvent> {
+        if let Some(x) = self.pairings.get_mut(&connection) {
+            x.0 = state;
+        }
+        None
+    }
+
+    fn answered(&mut self, connection: u8, state: PairingState) -> Option<()> {
+        let x = self.pairings.get_mut(&connection)?;
+        if x.0 != state {
+            return None;
+        }
+        x.0 = PairingState::Pairing;
+        Some(())
+    }
+}
@@ -3,6 +3,29 @@ use error::Error;
 use num_traits::FromPrimitive;
 use std::io::{Cursor, Read};
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct add_to_whitelist {
+    pub result: Error,
+}
+
+impl From<&[u8]> for add_to_whitelist {
+    fn from(data: &[u8]) -> add_to_whitelist {
+        let mut cursor = Cursor::new(data);
+        add_to_whitelist {
+            result: FromPrimitive::from_u16(cursor.get_u16_le()).unwrap(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for add_to_whitelist {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u16_le(self.result.clone() as u16);
+        bytes
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct bonding_confirm {
@@ -233,6 +256,29 @@ impl Into<Vec<u8>> for set_debug_mode {
     }
 }
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct set_minimum_key_size {
+    pub result: Error,
+}
+
+impl From<&[u8]> for set_minimum_key_size {
+    fn from(data: &[u8]) -> set_minimum_key_size {
+        let mut cursor = Cursor::new(data);
+        set_minimum_key_size {
+            result: FromPrimitive::from_u16(cursor.get_u16_le()).unwrap(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for set_minimum_key_size {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u16_le(self.result.clone() as u16);
+        bytes
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct set_oob_data {
@@ -0,0 +1,112 @@
+use bytes::{Buf, BufMut};
+use le_gap::AddressType;
+use message::{Message, MessageClass, MessageHeader, MessagePayload, MessageType};
+use num_traits::FromPrimitive;
+use std::io::{Cursor, Read};
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct close {
+    pub sync: u8,
+}
+
+impl close {
+    pub fn new(sync: u8) -> Message {
+        let header = MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x01,
+            message_class: MessageClass::sync,
+            message_id: 0x01,
+        };
+        let payload = close { sync };
+        let payload = MessagePayload::cmd_sync_close(payload);
+        Message { header, payload }
+    }
+}
+
+impl From<&[u8]> for close {
+    fn from(data: &[u8]) -> close {
+        let mut cursor = Cursor::new(data);
+        close {
+            sync: cursor.get_u8(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for close {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u8(self.sync);
+        bytes
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct open {
+    pub adv_sid: u8,
+    pub skip: u16,
+    pub timeout: u16,
+    pub address: [u8; 6],
+    pub address_type: AddressType,
+}
+
+impl open {
+    pub fn new(
+        adv_sid: u8,
+        skip: u16,
+        timeout: u16,
+        address: [u8; 6],
+        address_type: AddressType,
+    ) -> Message {
+        let header = MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x0c,
+            message_class: MessageClass::sync,
+            message_id: 0x00,
+        };
+        let payload = open {
+            adv_sid,
+            skip,
+            timeout,
+            address,
+            address_type,
+        };
+        let payload = MessagePayload::cmd_sync_open(payload);
+        Message { header, payload }
+    }
+}
+
+impl From<&[u8]> for open {
+    fn from(data: &[u8]) -> open {
+        let mut cursor = Cursor::new(data);
+        let adv_sid = cursor.get_u8();
+        let skip = cursor.get_u16_le();
+        let timeout = cursor.get_u16_le();
+        let mut address: [u8; 6] = Default::default();
+        cursor
+            .read_exact(&mut address)
+            .expect("Failed to read bytes.");
+        address.reverse();
+        let address_type = FromPrimitive::from_u8(cursor.get_u8()).unwrap();
+        open {
+            adv_sid,
+            skip,
+            timeout,
+            address,
+            address_type,
+        }
+    }
+}
+
+impl Into<Vec<u8>> for open {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u8(self.adv_sid);
+        bytes.put_u16_le(self.skip);
+        bytes.put_u16_le(self.timeout);
+        bytes.extend(self.address.iter().rev());
+        bytes.put_u8(self.address_type.clone() as u8);
+        bytes
+    }
+}
@@ -0,0 +1,128 @@
+use bytes::{Buf, BufMut};
+use error::Error;
+use le_gap::{AddressType, DataStatus, PhyType};
+use num_traits::FromPrimitive;
+use std::io::{Cursor, Read};
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct closed {
+    pub reason: Error,
+    pub sync: u8,
+}
+
+impl From<&[u8]> for closed {
+    fn from(data: &[u8]) -> closed {
+        let mut cursor = Cursor::new(data);
+        closed {
+            reason: FromPrimitive::from_u16(cursor.get_u16_le()).unwrap(),
+            sync: cursor.get_u8(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for closed {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u16_le(self.reason.clone() as u16);
+        bytes.put_u8(self.sync);
+        bytes
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct data {
+    pub sync: u8,
+    pub tx_power: i8,
+    pub rssi: i8,
+    pub data_status: DataStatus,
+    pub data: Vec<u8>,
+}
+
+impl From<&[u8]> for data {
+    fn from(data: &[u8]) -> data {
+        let mut cursor = Cursor::new(data);
+        let sync = cursor.get_u8();
+        let tx_power = cursor.get_i8();
+        let rssi = cursor.get_i8();
+        let data_status = FromPrimitive::from_u8(cursor.get_u8()).unwrap();
+        let mut data = Vec::new();
+        cursor.get_u8();
+        cursor
+            .read_to_end(&mut data)
+            .expect("Failed to read bytes.");
+        data {
+            sync,
+            tx_power,
+            rssi,
+            data_status,
+            data,
+        }
+    }
+}
+
+impl Into<Vec<u8>> for data {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u8(self.sync);
+        bytes.put_i8(self.tx_power);
+        bytes.put_i8(self.rssi);
+        bytes.put_u8(self.data_status.clone() as u8);
+        bytes.put_u8(self.data.len() as u8);
+        bytes.extend(self.data.iter());
+        bytes
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct opened {
+    pub sync: u8,
+    pub adv_sid: u8,
+    pub address: [u8; 6],
+    pub address_type: AddressType,
+    pub adv_phy: PhyType,
+    pub adv_interval: u16,
+    pub clock_accuracy: u16,
+}
+
+impl From<&[u8]> for opened {
+    fn from(data: &[u8]) -> opened {
+        let mut cursor = Cursor::new(data);
+        let sync = cursor.get_u8();
+        let adv_sid = cursor.get_u8();
+        let mut address: [u8; 6] = Default::default();
+        cursor
+            .read_exact(&mut address)
+            .expect("Failed to read bytes.");
+        address.reverse();
+        let address_type = FromPrimitive::from_u8(cursor.get_u8()).unwrap();
+        let adv_phy = FromPrimitive::from_u8(cursor.get_u8()).unwrap();
+        let adv_interval = cursor.get_u16_le();
+        let clock_accuracy = cursor.get_u16_le();
+        opened {
+            sync,
+            adv_sid,
+            address,
+            address_type,
+            adv_phy,
+            adv_interval,
+            clock_accuracy,
+        }
+    }
+}
+
+impl Into<Vec<u8>> for opened {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u8(self.sync);
+        bytes.put_u8(self.adv_sid);
+        bytes.extend(self.address.iter().rev());
+        bytes.put_u8(self.address_type.clone() as u8);
+        bytes.put_u8(self.adv_phy.clone() as u8);
+        bytes.put_u16_le(self.adv_interval);
+        bytes.put_u16_le(self.clock_accuracy);
+        bytes
+    }
+}
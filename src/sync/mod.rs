@@ -0,0 +1,47 @@
+pub mod cmd;
+pub mod evt;
+pub mod rsp;
+
+use message::{MessageClass, MessageHeader, MessagePayload, MessageType};
+use std::io::{Error, ErrorKind};
+
+pub fn parse(header: &MessageHeader, buffer: &[u8]) -> Result<MessagePayload, Error> {
+    match header {
+        MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x02,
+            message_class: MessageClass::sync,
+            message_id: 0x01,
+        } => Ok(MessagePayload::rsp_sync_close(rsp::close::from(buffer))),
+
+        MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x03,
+            message_class: MessageClass::sync,
+            message_id: 0x00,
+        } => Ok(MessagePayload::rsp_sync_open(rsp::open::from(buffer))),
+
+        MessageHeader {
+            message_type: MessageType::event,
+            payload_length: 0x03,
+            message_class: MessageClass::sync,
+            message_id: 0x01,
+        } => Ok(MessagePayload::evt_sync_closed(evt::closed::from(buffer))),
+
+        MessageHeader {
+            message_type: MessageType::event,
+            payload_length: _,
+            message_class: MessageClass::sync,
+            message_id: 0x02,
+        } => Ok(MessagePayload::evt_sync_data(evt::data::from(buffer))),
+
+        MessageHeader {
+            message_type: MessageType::event,
+            payload_length: 0x0e,
+            message_class: MessageClass::sync,
+            message_id: 0x00,
+        } => Ok(MessagePayload::evt_sync_opened(evt::opened::from(buffer))),
+
+        _ => Err(Error::from(ErrorKind::InvalidData)),
+    }
+}
@@ -0,0 +1,53 @@
+use bytes::{Buf, BufMut};
+use error::Error;
+use num_traits::FromPrimitive;
+use std::io::Cursor;
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct close {
+    pub result: Error,
+}
+
+impl From<&[u8]> for close {
+    fn from(data: &[u8]) -> close {
+        let mut cursor = Cursor::new(data);
+        close {
+            result: FromPrimitive::from_u16(cursor.get_u16_le()).unwrap(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for close {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u16_le(self.result.clone() as u16);
+        bytes
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct open {
+    pub result: Error,
+    pub sync: u8,
+}
+
+impl From<&[u8]> for open {
+    fn from(data: &[u8]) -> open {
+        let mut cursor = Cursor::new(data);
+        open {
+            result: FromPrimitive::from_u16(cursor.get_u16_le()).unwrap(),
+            sync: cursor.get_u8(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for open {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u16_le(self.result.clone() as u16);
+        bytes.put_u8(self.sync);
+        bytes
+    }
+}
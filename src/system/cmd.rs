@@ -2,6 +2,47 @@ use bytes::{Buf, BufMut};
 use message::{Message, MessageClass, MessageHeader, MessagePayload, MessageType};
 use std::io::{Cursor, Read};
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct data_buffer_write {
+    pub data: Vec<u8>,
+}
+
+impl data_buffer_write {
+    pub fn new(data: Vec<u8>) -> Message {
+        let header = MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 1 + data.len() as u8,
+            message_class: MessageClass::system,
+            message_id: 0x12,
+        };
+        let payload = data_buffer_write { data };
+        let payload = MessagePayload::cmd_system_data_buffer_write(payload);
+        Message { header, payload }
+    }
+}
+
+impl From<&[u8]> for data_buffer_write {
+    fn from(data: &[u8]) -> data_buffer_write {
+        let mut cursor = Cursor::new(data);
+        let mut data = Vec::new();
+        cursor.get_u8();
+        cursor
+            .read_to_end(&mut data)
+            .expect("Failed to read bytes.");
+        data_buffer_write { data }
+    }
+}
+
+impl Into<Vec<u8>> for data_buffer_write {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u8(self.data.len() as u8);
+        bytes.extend(self.data.iter());
+        bytes
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct get_bt_address {}
@@ -7,6 +7,15 @@ use std::io::{Error, ErrorKind};
 
 pub fn parse(header: &MessageHeader, buffer: &[u8]) -> Result<MessagePayload, Error> {
     match header {
+        MessageHeader {
+            message_type: MessageType::command_response,
+            payload_length: 0x02,
+            message_class: MessageClass::system,
+            message_id: 0x12,
+        } => Ok(MessagePayload::rsp_system_data_buffer_write(
+            rsp::data_buffer_write::from(buffer),
+        )),
+
         MessageHeader {
             message_type: MessageType::command_response,
             payload_length: 0x06,
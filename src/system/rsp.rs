@@ -3,6 +3,29 @@ use error::Error;
 use num_traits::FromPrimitive;
 use std::io::{Cursor, Read};
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct data_buffer_write {
+    pub result: Error,
+}
+
+impl From<&[u8]> for data_buffer_write {
+    fn from(data: &[u8]) -> data_buffer_write {
+        let mut cursor = Cursor::new(data);
+        data_buffer_write {
+            result: FromPrimitive::from_u16(cursor.get_u16_le()).unwrap(),
+        }
+    }
+}
+
+impl Into<Vec<u8>> for data_buffer_write {
+    fn into(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.put_u16_le(self.result.clone() as u16);
+        bytes
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct get_bt_address {